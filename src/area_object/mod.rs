@@ -5,6 +5,8 @@ use glib::Object;
 use gtk::glib;
 use serde::{Deserialize, Serialize};
 
+use crate::{ocr_object::OcrOptions, preprocess::Preprocess};
+
 glib::wrapper! {
     pub struct AreaObject(ObjectSubclass<imp::AreaObject>);
 }
@@ -24,14 +26,20 @@ impl AreaObject {
         self.imp().data.borrow().clone()
     }
 
+    pub fn set_area_data(&self, area_data: AreaData) {
+        self.imp().data.replace(area_data);
+    }
+
     pub fn from_area_data(area_data: AreaData) -> Self {
-        Self::new(
+        let area = Self::new(
             area_data.x,
             area_data.y,
             area_data.width,
             area_data.height,
-            area_data.text,
-        )
+            area_data.text.clone(),
+        );
+        area.imp().data.replace(area_data);
+        area
     }
 }
 
@@ -41,6 +49,19 @@ pub struct AreaData {
     pub y: i32,
     pub width: i32,
     pub height: i32,
+    #[serde(default)]
+    pub preprocess: Preprocess,
+    #[serde(default)]
+    pub ocr: OcrOptions,
+    /// Captures in a row the area must stay unchanged for before it is read.
+    #[serde(default)]
+    pub stable_captures: u32,
     #[serde(skip_serializing, default)]
     pub text: String,
+    /// Name of the provider that translated `text`.
+    #[serde(skip_serializing, default)]
+    pub provider: String,
+    /// Mean confidence, from 0 to 100, of the words Tesseract read.
+    #[serde(skip_serializing, default)]
+    pub confidence: f32,
 }
@@ -0,0 +1,136 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use unicode_normalization::UnicodeNormalization;
+
+/// Characters Tesseract reads from borders, cursors and text box decorations.
+/// Removed before NFKC, so the full width forms it turns into these survive.
+const NOISE: [char; 2] = ['|', '¦'];
+
+/// Line endings after which the next line starts a new sentence.
+const SENTENCE_ENDS: [char; 16] = [
+    '.', '!', '?', ':', '…', '"', '”', '»', '。', '！', '？', '」', '』', '）', ')', '】',
+];
+
+static HYPHENATED: Lazy<Regex> = Lazy::new(|| Regex::new(r"(\w)[-‐]\n[ \t]*(\w)").unwrap());
+static SPACES: Lazy<Regex> = Lazy::new(|| Regex::new(r"[^\S\n]+").unwrap());
+static BLANK_LINES: Lazy<Regex> = Lazy::new(|| Regex::new(r"\n{3,}").unwrap());
+
+/// Steps applied to the OCR text before it is translated, all off unless the
+/// profile turns them on.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CleanupOptions {
+    /// Joins lines broken by the text box width, keeping the sentence ends.
+    pub join_lines: bool,
+    /// Joins words split with a hyphen at the end of a line.
+    pub dehyphenate: bool,
+    /// Removes `|` and `¦`.
+    pub strip_noise: bool,
+    /// Unicode NFKC, turning full width latin and half width kana into their
+    /// usual forms.
+    pub nfkc: bool,
+    /// Turns runs of spaces into one and trims every line.
+    pub collapse_whitespace: bool,
+}
+
+pub fn clean(text: &str, options: &CleanupOptions) -> String {
+    let mut text = text.to_string();
+    if options.strip_noise {
+        text.retain(|c| !NOISE.contains(&c));
+    }
+    if options.nfkc {
+        text = text.nfkc().collect();
+    }
+    if options.collapse_whitespace {
+        text = SPACES.replace_all(&text, " ").into_owned();
+        text = text
+            .lines()
+            .map(str::trim)
+            .collect::<Vec<&str>>()
+            .join("\n");
+        text = BLANK_LINES.replace_all(&text, "\n\n").into_owned();
+    }
+    if options.dehyphenate {
+        text = HYPHENATED.replace_all(&text, "$1$2").into_owned();
+    }
+    if options.join_lines {
+        text = join_lines(&text);
+    }
+    text.trim().to_string()
+}
+
+/// Blank lines still separate paragraphs.
+fn join_lines(text: &str) -> String {
+    let mut joined = String::with_capacity(text.len());
+    for line in text.lines() {
+        let line = line.trim_end();
+        match joined.chars().last() {
+            None => {}
+            Some('\n') => {}
+            Some(_) if line.is_empty() => joined.push('\n'),
+            Some(last) if SENTENCE_ENDS.contains(&last) => joined.push('\n'),
+            Some(last) if is_cjk(last) => {}
+            Some(_) => joined.push(' '),
+        }
+        if line.is_empty() {
+            if !joined.is_empty() && !joined.ends_with("\n\n") {
+                joined.push('\n');
+            }
+            continue;
+        }
+        joined.push_str(line.trim_start());
+    }
+    joined
+}
+
+/// Scripts written without spaces between words.
+fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{3040}'..='\u{30ff}' // Hiragana and Katakana
+        | '\u{3400}'..='\u{4dbf}' // CJK Extension A
+        | '\u{4e00}'..='\u{9fff}' // CJK Unified Ideographs
+        | '\u{f900}'..='\u{faff}' // CJK Compatibility Ideographs
+        | '\u{ff66}'..='\u{ff9f}' // Half width Katakana
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL: CleanupOptions = CleanupOptions {
+        join_lines: true,
+        dehyphenate: true,
+        strip_noise: true,
+        nfkc: true,
+        collapse_whitespace: true,
+    };
+
+    #[test]
+    fn default_keeps_the_text() {
+        assert_eq!(
+            clean("ね～  ｜ＡＢ", &CleanupOptions::default()),
+            "ね～  ｜ＡＢ"
+        );
+    }
+
+    #[test]
+    fn wave_dash_survives_every_step() {
+        assert_eq!(clean("ね～", &ALL), "ね~");
+        assert_eq!(clean("ね～\nそうだね～", &ALL), "ね~ そうだね~");
+    }
+
+    #[test]
+    fn noise_is_stripped() {
+        assert_eq!(clean("| Hello ¦", &ALL), "Hello");
+    }
+
+    #[test]
+    fn wrapped_lines_are_joined() {
+        assert_eq!(
+            clean("a long sen-\ntence that\nwraps.\nNext", &ALL),
+            "a long sentence that wraps.\nNext"
+        );
+    }
+}
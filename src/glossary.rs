@@ -0,0 +1,169 @@
+use anyhow::{Context, Result};
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Matches the placeholders even when the provider adds spaces inside them.
+static PLACEHOLDER: Lazy<Regex> = Lazy::new(|| Regex::new(r"\[\s*#\s*(\d+)\s*\]").unwrap());
+
+/// Term that must always be translated as `target`.
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GlossaryEntry {
+    pub source: String,
+    pub target: String,
+    #[serde(default)]
+    pub case_sensitive: bool,
+    #[serde(default)]
+    pub whole_word: bool,
+}
+
+/// Hides the glossary terms from the provider behind placeholders.
+pub struct Glossary {
+    regex: Option<Regex>,
+    targets: Vec<String>,
+}
+
+/// Text ready to be translated and the terms its placeholders stand for.
+pub struct Protected {
+    pub text: String,
+    terms: Vec<String>,
+}
+
+impl Glossary {
+    pub fn new(entries: &[GlossaryEntry]) -> Result<Self> {
+        let mut entries = entries
+            .iter()
+            .filter(|entry| !entry.source.trim().is_empty())
+            .collect::<Vec<&GlossaryEntry>>();
+        // Longer terms first so they win over the terms they contain.
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.source.chars().count()));
+
+        let pattern = entries
+            .iter()
+            .map(|entry| {
+                let flags = if entry.case_sensitive { "" } else { "(?i)" };
+                let source = entry.source.trim();
+                let term = regex::escape(source);
+                if entry.whole_word {
+                    // A boundary only makes sense next to a word character,
+                    // "Mr." or "(HP)" end in punctuation.
+                    let boundary = |c: Option<char>| {
+                        if c.is_some_and(|c| c.is_alphanumeric() || c == '_') {
+                            r"\b"
+                        } else {
+                            ""
+                        }
+                    };
+                    let start = boundary(source.chars().next());
+                    let end = boundary(source.chars().last());
+                    format!("({flags}{start}{term}{end})")
+                } else {
+                    format!("({flags}{term})")
+                }
+            })
+            .collect::<Vec<String>>()
+            .join("|");
+
+        let regex = if entries.is_empty() {
+            None
+        } else {
+            Some(Regex::new(&pattern).context("Invalid glossary")?)
+        };
+        Ok(Glossary {
+            regex,
+            targets: entries.iter().map(|entry| entry.target.clone()).collect(),
+        })
+    }
+
+    pub fn protect(&self, text: &str) -> Protected {
+        let Some(regex) = &self.regex else {
+            return Protected {
+                text: text.to_string(),
+                terms: vec![],
+            };
+        };
+        let mut terms = vec![];
+        let text = regex.replace_all(text, |captures: &Captures| {
+            let index = (1..captures.len())
+                .find(|i| captures.get(*i).is_some())
+                .map_or(0, |i| i - 1);
+            terms.push(self.targets[index].clone());
+            format!("[#{}]", terms.len() - 1)
+        });
+        Protected {
+            text: text.to_string(),
+            terms,
+        }
+    }
+}
+
+impl Protected {
+    /// Puts the glossary terms back in place of the placeholders.
+    pub fn restore(&self, translated: &str) -> String {
+        if self.terms.is_empty() {
+            return translated.to_string();
+        }
+        PLACEHOLDER
+            .replace_all(translated, |captures: &Captures| {
+                captures[1]
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|i| self.terms.get(i))
+                    .cloned()
+                    .unwrap_or_else(|| captures[0].to_string())
+            })
+            .to_string()
+    }
+}
+
+pub fn import_csv(path: &Path) -> Result<Vec<GlossaryEntry>> {
+    let mut reader = csv::Reader::from_path(path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    let entries = reader
+        .deserialize()
+        .collect::<Result<Vec<GlossaryEntry>, csv::Error>>()?;
+    Ok(entries)
+}
+
+pub fn export_csv(path: &Path, entries: &[GlossaryEntry]) -> Result<()> {
+    let mut writer = csv::Writer::from_path(path)
+        .with_context(|| format!("Failed to create {}", path.display()))?;
+    for entry in entries {
+        writer.serialize(entry)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn whole_word(source: &str, target: &str) -> GlossaryEntry {
+        GlossaryEntry {
+            source: source.to_string(),
+            target: target.to_string(),
+            case_sensitive: true,
+            whole_word: true,
+        }
+    }
+
+    #[test]
+    fn whole_words_ending_in_punctuation() {
+        let glossary =
+            Glossary::new(&[whole_word("Mr.", "Sr."), whole_word("(HP)", "(PV)")]).unwrap();
+        let protected = glossary.protect("Mr. Smith (HP) Mr.Smith");
+        assert_eq!(protected.text, "[#0] Smith [#1] [#2]Smith");
+        assert_eq!(
+            protected.restore(&protected.text),
+            "Sr. Smith (PV) Sr.Smith"
+        );
+    }
+
+    #[test]
+    fn whole_words_inside_other_words() {
+        let glossary = Glossary::new(&[whole_word("HP", "PV")]).unwrap();
+        assert_eq!(glossary.protect("HP HPS").text, "[#0] HPS");
+    }
+}
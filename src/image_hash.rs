@@ -0,0 +1,144 @@
+use image::{imageops, GrayImage, RgbaImage};
+
+/// Thumbnail width, wide enough for a new glyph in a dialogue box to show.
+const WIDTH: u32 = 128;
+const MAX_HEIGHT: u32 = 64;
+/// Brightness changes up to this are taken as capture noise.
+const NOISE: u8 = 16;
+/// Capture pixels that may change before the capture counts as changed, about
+/// a blinking cursor and less than a glyph.
+const TOLERANCE: u32 = 16;
+
+/// Small grayscale copy of a capture, compared with the one of the next
+/// capture to tell whether the area changed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageHash {
+    thumbnail: GrayImage,
+    /// Capture pixels behind each pixel of the thumbnail.
+    scale: u32,
+}
+
+impl ImageHash {
+    pub fn new(image: &RgbaImage) -> Self {
+        let (width, height) = image.dimensions();
+        let thumbnail_width = width.clamp(1, WIDTH);
+        let thumbnail_height = (height as u64 * thumbnail_width as u64 / width.max(1) as u64)
+            .clamp(1, MAX_HEIGHT as u64) as u32;
+        let thumbnail = imageops::resize(
+            image,
+            thumbnail_width,
+            thumbnail_height,
+            imageops::FilterType::Triangle,
+        );
+        let scale = (width as u64 * height as u64 / (thumbnail_width * thumbnail_height) as u64)
+            .clamp(1, u32::MAX as u64) as u32;
+        Self {
+            thumbnail: imageops::grayscale(&thumbnail),
+            scale,
+        }
+    }
+
+    /// Pixels of the thumbnail that changed by more than noise.
+    pub fn distance(&self, other: &ImageHash) -> u32 {
+        if self.thumbnail.dimensions() != other.thumbnail.dimensions() {
+            return u32::MAX;
+        }
+        self.thumbnail
+            .pixels()
+            .zip(other.thumbnail.pixels())
+            .filter(|(pixel, other)| pixel.0[0].abs_diff(other.0[0]) > NOISE)
+            .count() as u32
+    }
+
+    /// Thumbnail pixels that may change, fewer the more the capture was shrunk
+    /// since one of them then covers a whole glyph.
+    pub fn tolerance(&self) -> u32 {
+        TOLERANCE / self.scale
+    }
+
+    /// Whether `other` shows the same thing, give or take noise.
+    pub fn matches(&self, other: &ImageHash) -> bool {
+        self.distance(other) <= self.tolerance()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    const BACKGROUND: Rgba<u8> = Rgba([20, 20, 60, 255]);
+    const TEXT: Rgba<u8> = Rgba([255, 255, 255, 255]);
+
+    /// Draws a solid block the size of a glyph.
+    fn glyph(image: &mut RgbaImage, x: u32, y: u32) {
+        for x in x..x + 10 {
+            for y in y..y + 14 {
+                image.put_pixel(x, y, TEXT);
+            }
+        }
+    }
+
+    #[test]
+    fn thumbnail_keeps_small_captures() {
+        let hash = ImageHash::new(&RgbaImage::from_pixel(100, 30, BACKGROUND));
+        assert_eq!(hash.thumbnail.dimensions(), (100, 30));
+        assert_eq!(hash.scale, 1);
+        assert_eq!(hash.tolerance(), TOLERANCE);
+    }
+
+    #[test]
+    fn thumbnail_is_clamped() {
+        let wide = ImageHash::new(&RgbaImage::from_pixel(1920, 1080, BACKGROUND));
+        assert_eq!(wide.thumbnail.dimensions(), (WIDTH, MAX_HEIGHT));
+        assert_eq!(wide.scale, 1920 * 1080 / (WIDTH * MAX_HEIGHT));
+        assert_eq!(wide.tolerance(), 0);
+
+        let tall = ImageHash::new(&RgbaImage::from_pixel(10, 1000, BACKGROUND));
+        assert_eq!(tall.thumbnail.dimensions(), (10, MAX_HEIGHT));
+
+        let line = ImageHash::new(&RgbaImage::from_pixel(1000, 1, BACKGROUND));
+        assert_eq!(line.thumbnail.dimensions(), (WIDTH, 1));
+    }
+
+    #[test]
+    fn distance_counts_changed_pixels() {
+        let image = RgbaImage::from_pixel(100, 30, BACKGROUND);
+        let hash = ImageHash::new(&image);
+        assert_eq!(hash.distance(&hash), 0);
+
+        let mut noisy = image.clone();
+        noisy.put_pixel(5, 5, Rgba([30, 30, 70, 255]));
+        assert_eq!(hash.distance(&ImageHash::new(&noisy)), 0);
+
+        let mut changed = image.clone();
+        changed.put_pixel(5, 5, TEXT);
+        changed.put_pixel(6, 5, TEXT);
+        assert_eq!(hash.distance(&ImageHash::new(&changed)), 2);
+
+        let resized = ImageHash::new(&RgbaImage::from_pixel(50, 30, BACKGROUND));
+        assert_eq!(hash.distance(&resized), u32::MAX);
+    }
+
+    #[test]
+    fn small_changes_are_tolerated() {
+        let image = RgbaImage::from_pixel(100, 30, BACKGROUND);
+        let mut cursor = image.clone();
+        for y in 5..15 {
+            cursor.put_pixel(50, y, TEXT);
+        }
+        assert!(ImageHash::new(&image).matches(&ImageHash::new(&cursor)));
+
+        let mut text = image.clone();
+        glyph(&mut text, 50, 5);
+        assert!(!ImageHash::new(&image).matches(&ImageHash::new(&text)));
+    }
+
+    #[test]
+    fn one_glyph_changes_a_whole_window() {
+        let image = RgbaImage::from_pixel(1920, 1080, BACKGROUND);
+        let mut text = image.clone();
+        glyph(&mut text, 960, 540);
+        assert!(!ImageHash::new(&image).matches(&ImageHash::new(&text)));
+    }
+}
@@ -1,12 +1,18 @@
 #![windows_subsystem = "windows"]
 
 mod area_object;
+mod cleanup;
+mod glossary;
+mod image_hash;
 mod ocr_object;
 mod paint;
+mod preprocess;
 mod profile_object;
+mod rewrite;
 mod screen_object;
 mod settings;
 mod state;
+mod tesseract;
 mod translation;
 mod translator_object;
 mod utils;
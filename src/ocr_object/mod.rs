@@ -1,11 +1,13 @@
 mod imp;
 
 use crate::area_object::AreaData;
-use crate::{screen_object::ScreenData, utils};
+use crate::tesseract;
 use anyhow::Result;
 use glib::Object;
 use gtk::glib;
-use rusty_tesseract::Image;
+use image::{DynamicImage, RgbaImage};
+use rusty_tesseract::{Args, Data};
+use serde::{Deserialize, Serialize};
 
 use crate::translator_object::TranslatorData;
 
@@ -23,6 +25,206 @@ impl OcrObject {
             .build()
     }
 }
+/// Tesseract options of one area, the ones left unset come from the profile.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OcrOptions {
+    /// Tesseract language code, like `jpn` or `eng`.
+    pub language: String,
+    pub psm: Option<i32>,
+    pub oem: Option<i32>,
+    pub dpi: Option<i32>,
+    /// Only these characters are recognized.
+    pub whitelist: String,
+    /// These characters are never recognized.
+    pub blacklist: String,
+}
+
+impl OcrOptions {
+    pub const PSM: [(i32, &'static str); 9] = [
+        (3, "Automatic"),
+        (4, "Single column"),
+        (5, "Vertical block"),
+        (6, "Single block"),
+        (7, "Single line"),
+        (8, "Single word"),
+        (10, "Single character"),
+        (11, "Sparse text"),
+        (13, "Raw line"),
+    ];
+    pub const OEM: [(i32, &'static str); 3] = [
+        (0, "Legacy"),
+        (1, "Neural network (LSTM)"),
+        (2, "Legacy and LSTM"),
+    ];
+
+    pub fn args(&self, language: &str) -> Args {
+        let mut args = Args {
+            lang: self.language(language).to_owned(),
+            ..Default::default()
+        };
+        if self.psm.is_some() {
+            args.psm = self.psm;
+        }
+        if self.oem.is_some() {
+            args.oem = self.oem;
+        }
+        if self.dpi.is_some() {
+            args.dpi = self.dpi;
+        }
+        if !self.whitelist.is_empty() {
+            args.config_variables
+                .insert("tessedit_char_whitelist".into(), self.whitelist.clone());
+        }
+        if !self.blacklist.is_empty() {
+            args.config_variables
+                .insert("tessedit_char_blacklist".into(), self.blacklist.clone());
+        }
+        args
+    }
+
+    pub fn language<'a>(&'a self, default: &'a str) -> &'a str {
+        if self.language.is_empty() {
+            default
+        } else {
+            &self.language
+        }
+    }
+}
+
+/// Tesseract language codes and their names.
+const LANGUAGES: [(&str, &str); 129] = [
+    ("afr", "Afrikaans"),
+    ("amh", "Amharic"),
+    ("ara", "Arabic"),
+    ("asm", "Assamese"),
+    ("aze", "Azerbaijani"),
+    ("aze_cyrl", "Azerbaijani - Cyrilic"),
+    ("bel", "Belarusian"),
+    ("ben", "Bengali"),
+    ("bod", "Tibetan"),
+    ("bos", "Bosnian"),
+    ("bre", "Breton"),
+    ("bul", "Bulgarian"),
+    ("cat", "Catalan; Valencian"),
+    ("ceb", "Cebuano"),
+    ("ces", "Czech"),
+    ("chi_sim", "Chinese - Simplified"),
+    ("chi_sim_vert", "Chinese - Simplified Vertical"),
+    ("chi_tra", "Chinese - Traditional"),
+    ("chi_tra_vert", "Chinese - Traditional Vertical"),
+    ("chr", "Cherokee"),
+    ("cos", "Corsican"),
+    ("cym", "Welsh"),
+    ("dan", "Danish"),
+    ("dan_frak", "Danish - Fraktur (contrib)"),
+    ("deu", "German"),
+    ("deu_frak", "German - Fraktur (contrib)"),
+    ("dzo", "Dzongkha"),
+    ("ell", "Greek, Modern (1453-)"),
+    ("eng", "English"),
+    ("enm", "English, Middle (1100-1500)"),
+    ("epo", "Esperanto"),
+    ("equ", "Math / equation detection module"),
+    ("est", "Estonian"),
+    ("eus", "Basque"),
+    ("fao", "Faroese"),
+    ("fas", "Persian"),
+    ("fil", "Filipino (old - Tagalog)"),
+    ("fin", "Finnish"),
+    ("fra", "French"),
+    ("frk", "German - Fraktur"),
+    ("frm", "French, Middle (ca.1400-1600)"),
+    ("fry", "Western Frisian"),
+    ("gla", "Scottish Gaelic"),
+    ("gle", "Irish"),
+    ("glg", "Galician"),
+    ("grc", "Greek, Ancient (to 1453) (contrib)"),
+    ("guj", "Gujarati"),
+    ("hat", "Haitian; Haitian Creole"),
+    ("heb", "Hebrew"),
+    ("hin", "Hindi"),
+    ("hrv", "Croatian"),
+    ("hun", "Hungarian"),
+    ("hye", "Armenian"),
+    ("iku", "Inuktitut"),
+    ("ind", "Indonesian"),
+    ("isl", "Icelandic"),
+    ("ita", "Italian"),
+    ("ita_old", "Italian - Old"),
+    ("jav", "Javanese"),
+    ("jpn", "Japanese"),
+    ("jpn_vert", "Japanese Vertical"),
+    ("kan", "Kannada"),
+    ("kat", "Georgian"),
+    ("kat_old", "Georgian - Old"),
+    ("kaz", "Kazakh"),
+    ("khm", "Central Khmer"),
+    ("kir", "Kirghiz; Kyrgyz"),
+    ("kmr", "Kurmanji (Kurdish - Latin Script)"),
+    ("kor", "Korean"),
+    ("kor_vert", "Korean (vertical)"),
+    ("kur", "Kurdish (Arabic Script)"),
+    ("lao", "Lao"),
+    ("lat", "Latin"),
+    ("lav", "Latvian"),
+    ("lit", "Lithuanian"),
+    ("ltz", "Luxembourgish"),
+    ("mal", "Malayalam"),
+    ("mar", "Marathi"),
+    ("mkd", "Macedonian"),
+    ("mlt", "Maltese"),
+    ("mon", "Mongolian"),
+    ("mri", "Maori"),
+    ("msa", "Malay"),
+    ("mya", "Burmese"),
+    ("nep", "Nepali"),
+    ("nld", "Dutch; Flemish"),
+    ("nor", "Norwegian"),
+    ("oci", "Occitan (post 1500)"),
+    ("ori", "Oriya"),
+    ("osd", "Orientation and script detection module"),
+    ("pan", "Panjabi; Punjabi"),
+    ("pol", "Polish"),
+    ("por", "Portuguese"),
+    ("pus", "Pushto; Pashto"),
+    ("que", "Quechua"),
+    ("ron", "Romanian; Moldavian; Moldovan"),
+    ("rus", "Russian"),
+    ("san", "Sanskrit"),
+    ("sin", "Sinhala; Sinhalese"),
+    ("slk", "Slovak"),
+    ("slk_frak", "Slovak - Fraktur (contrib)"),
+    ("slv", "Slovenian"),
+    ("snd", "Sindhi"),
+    ("spa", "Spanish; Castilian"),
+    ("spa_old", "Spanish; Castilian - Old"),
+    ("sqi", "Albanian"),
+    ("srp", "Serbian"),
+    ("srp_latn", "Serbian - Latin"),
+    ("sun", "Sundanese"),
+    ("swa", "Swahili"),
+    ("swe", "Swedish"),
+    ("syr", "Syriac"),
+    ("tam", "Tamil"),
+    ("tat", "Tatar"),
+    ("tel", "Telugu"),
+    ("tgk", "Tajik"),
+    ("tgl", "Tagalog (new - Filipino)"),
+    ("tha", "Thai"),
+    ("tir", "Tigrinya"),
+    ("ton", "Tonga"),
+    ("tur", "Turkish"),
+    ("uig", "Uighur; Uyghur"),
+    ("ukr", "Ukrainian"),
+    ("urd", "Urdu"),
+    ("uzb", "Uzbek"),
+    ("uzb_cyrl", "Uzbek - Cyrilic"),
+    ("vie", "Vietnamese"),
+    ("yid", "Yiddish"),
+    ("yor", "Yoruba"),
+];
+
 #[derive(Default, Clone)]
 pub struct OcrData {
     pub code: String,
@@ -37,7 +239,7 @@ impl OcrData {
             "nld" => "nl",
             "dan" => "da",
             "ces" => "cs",
-            "chi_sim" => "zh",
+            "chi_sim" | "chi_tra" | "chi_sim_vert" | "chi_tra_vert" => "zh",
             "bul" => "bg",
             "est" => "et",
             "fin" => "fi",
@@ -47,8 +249,8 @@ impl OcrData {
             "hun" => "hu",
             "ind" => "id",
             "ita" => "it",
-            "jpn" => "ja",
-            "kor" => "ko",
+            "jpn" | "jpn_vert" => "ja",
+            "kor" | "kor_vert" => "ko",
             "lav" => "lv",
             "lit" => "lt",
             "nor" => "nb",
@@ -62,142 +264,16 @@ impl OcrData {
             "swe" => "sv",
             "tur" => "tr",
             "ukr" => "uk",
-            _ => "auto",
+            _ => return TranslatorData::auto(),
         };
         TranslatorData::new(code)
     }
 
     pub fn new(code: &str) -> OcrData {
-        let language: &str = match code {
-            "afr" => "Afrikaans",
-            "amh" => "Amharic",
-            "ara" => "Arabic",
-            "asm" => "Assamese",
-            "aze" => "Azerbaijani",
-            "aze_cyrl" => "Azerbaijani - Cyrilic",
-            "bel" => "Belarusian",
-            "ben" => "Bengali",
-            "bod" => "Tibetan",
-            "bos" => "Bosnian",
-            "bre" => "Breton",
-            "bul" => "Bulgarian",
-            "cat" => "Catalan; Valencian",
-            "ceb" => "Cebuano",
-            "ces" => "Czech",
-            "chi_sim" => "Chinese - Simplified",
-            "chi_tra" => "Chinese - Traditional",
-            "chr" => "Cherokee",
-            "cos" => "Corsican",
-            "cym" => "Welsh",
-            "dan" => "Danish",
-            "dan_frak" => "Danish - Fraktur (contrib)",
-            "deu" => "German",
-            "deu_frak" => "German - Fraktur (contrib)",
-            "dzo" => "Dzongkha",
-            "ell" => "Greek, Modern (1453-)",
-            "eng" => "English",
-            "enm" => "English, Middle (1100-1500)",
-            "epo" => "Esperanto",
-            "equ" => "Math / equation detection module",
-            "est" => "Estonian",
-            "eus" => "Basque",
-            "fao" => "Faroese",
-            "fas" => "Persian",
-            "fil" => "Filipino (old - Tagalog)",
-            "fin" => "Finnish",
-            "fra" => "French",
-            "frk" => "German - Fraktur",
-            "frm" => "French, Middle (ca.1400-1600)",
-            "fry" => "Western Frisian",
-            "gla" => "Scottish Gaelic",
-            "gle" => "Irish",
-            "glg" => "Galician",
-            "grc" => "Greek, Ancient (to 1453) (contrib)",
-            "guj" => "Gujarati",
-            "hat" => "Haitian; Haitian Creole",
-            "heb" => "Hebrew",
-            "hin" => "Hindi",
-            "hrv" => "Croatian",
-            "hun" => "Hungarian",
-            "hye" => "Armenian",
-            "iku" => "Inuktitut",
-            "ind" => "Indonesian",
-            "isl" => "Icelandic",
-            "ita" => "Italian",
-            "ita_old" => "Italian - Old",
-            "jav" => "Javanese",
-            "jpn" => "Japanese",
-            "jpn_vert" => "Japanese Vertical",
-            "kan" => "Kannada",
-            "kat" => "Georgian",
-            "kat_old" => "Georgian - Old",
-            "kaz" => "Kazakh",
-            "khm" => "Central Khmer",
-            "kir" => "Kirghiz; Kyrgyz",
-            "kmr" => "Kurmanji (Kurdish - Latin Script)",
-            "kor" => "Korean",
-            "kor_vert" => "Korean (vertical)",
-            "kur" => "Kurdish (Arabic Script)",
-            "lao" => "Lao",
-            "lat" => "Latin",
-            "lav" => "Latvian",
-            "lit" => "Lithuanian",
-            "ltz" => "Luxembourgish",
-            "mal" => "Malayalam",
-            "mar" => "Marathi",
-            "mkd" => "Macedonian",
-            "mlt" => "Maltese",
-            "mon" => "Mongolian",
-            "mri" => "Maori",
-            "msa" => "Malay",
-            "mya" => "Burmese",
-            "nep" => "Nepali",
-            "nld" => "Dutch; Flemish",
-            "nor" => "Norwegian",
-            "oci" => "Occitan (post 1500)",
-            "ori" => "Oriya",
-            "osd" => "Orientation and script detection module",
-            "pan" => "Panjabi; Punjabi",
-            "pol" => "Polish",
-            "por" => "Portuguese",
-            "pus" => "Pushto; Pashto",
-            "que" => "Quechua",
-            "ron" => "Romanian; Moldavian; Moldovan",
-            "rus" => "Russian",
-            "san" => "Sanskrit",
-            "sin" => "Sinhala; Sinhalese",
-            "slk" => "Slovak",
-            "slk_frak" => "Slovak - Fraktur (contrib)",
-            "slv" => "Slovenian",
-            "snd" => "Sindhi",
-            "spa" => "Spanish; Castilian",
-            "spa_old" => "Spanish; Castilian - Old",
-            "sqi" => "Albanian",
-            "srp" => "Serbian",
-            "srp_latn" => "Serbian - Latin",
-            "sun" => "Sundanese",
-            "swa" => "Swahili",
-            "swe" => "Swedish",
-            "syr" => "Syriac",
-            "tam" => "Tamil",
-            "tat" => "Tatar",
-            "tel" => "Telugu",
-            "tgk" => "Tajik",
-            "tgl" => "Tagalog (new - Filipino)",
-            "tha" => "Thai",
-            "tir" => "Tigrinya",
-            "ton" => "Tonga",
-            "tur" => "Turkish",
-            "uig" => "Uighur; Uyghur",
-            "ukr" => "Ukrainian",
-            "urd" => "Urdu",
-            "uzb" => "Uzbek",
-            "uzb_cyrl" => "Uzbek - Cyrilic",
-            "vie" => "Vietnamese",
-            "yid" => "Yiddish",
-            "yor" => "Yoruba",
-            _ => "Invalid",
-        };
+        let language = LANGUAGES
+            .iter()
+            .find(|(language_code, _)| *language_code == code)
+            .map_or("Invalid", |(_, language)| language);
         OcrData {
             code: code.to_owned(),
             language: language.to_owned(),
@@ -205,67 +281,168 @@ impl OcrData {
         }
     }
 
-    pub fn ocr_areas(&self, areas: &Vec<AreaData>, screen: &ScreenData) -> Result<Vec<AreaData>> {
+    /// Reads a capture of `area` with its preprocessing and Tesseract options.
+    pub fn ocr_area(&self, image: RgbaImage, area: &AreaData) -> Result<AreaData> {
+        let image = area.preprocess.apply(image);
+        let data = tesseract::image_to_data(&image, &area.ocr.args(&self.code))?;
+        let text = group_words(&data, true)
+            .into_iter()
+            .map(|block| block.text)
+            .collect::<Vec<String>>()
+            .join("\n\n");
+        let (sum, count) =
+            words(&data).fold((0.0, 0), |(sum, count), word| (sum + word.conf, count + 1));
+        Ok(AreaData {
+            text,
+            confidence: if count > 0 { sum / count as f32 } else { 0.0 },
+            ..area.clone()
+        })
+    }
+
+    /// Reads a capture of the whole window, one area per line or, with
+    /// `paragraphs`, per text block. Each area keeps the mean confidence of
+    /// its words.
+    pub fn ocr_screen(&self, image: RgbaImage, paragraphs: bool) -> Result<Vec<AreaData>> {
         let default_args = rusty_tesseract::Args {
             lang: self.code.to_owned(),
             ..Default::default()
         };
-        let mut rects = vec![];
-        for path in screen.capture_areas(areas)? {
-            let image = Image::from_path(&path)?;
-            let text = rusty_tesseract::image_to_string(&image, &default_args)?
-                .trim()
-                .to_string();
-            utils::remove_file(&path)?;
-            rects.push(AreaData {
-                text,
-                ..areas[rects.len()].clone()
-            });
-        }
-        Ok(rects)
+        let data = tesseract::image_to_data(&DynamicImage::ImageRgba8(image), &default_args)?;
+        Ok(group_words(&data, paragraphs))
     }
+}
 
-    pub fn ocr_screen(&self, screen: &ScreenData) -> Result<Vec<AreaData>> {
-        let default_args = rusty_tesseract::Args {
-            lang: self.code.to_owned(),
-            ..Default::default()
+/// Words of one line, or of one block, and the box around them.
+struct Region {
+    key: (i32, i32, i32, i32),
+    line: (i32, i32, i32, i32),
+    left: i32,
+    top: i32,
+    right: i32,
+    bottom: i32,
+    text: String,
+    confidence: f32,
+    words: u32,
+}
+
+/// Recognized words, whatever their confidence.
+fn words(data: &[Data]) -> impl Iterator<Item = &Data> {
+    data.iter()
+        .filter(|word| word.level == 5 && word.conf >= 0.0 && !word.text.trim().is_empty())
+}
+
+/// Groups the words of Tesseract's TSV output by the line, or block, they
+/// belong to. Lines of a block are kept on their own row of the text.
+fn group_words(data: &[Data], paragraphs: bool) -> Vec<AreaData> {
+    let mut regions: Vec<Region> = vec![];
+    for word in words(data) {
+        let line = (word.page_num, word.block_num, word.par_num, word.line_num);
+        let key = if paragraphs {
+            (word.page_num, word.block_num, 0, 0)
+        } else {
+            line
         };
-        let path = screen.capture()?;
-        let image = Image::from_path(&path)?;
-        let output = rusty_tesseract::image_to_data(&image, &default_args)?;
-        utils::remove_file(&path)?;
-        let mut texts = Vec::new();
-        let mut line: AreaData = Default::default();
-        for dt in output.data {
-            if dt.conf <= 0.0 {
-                if line.text.trim().is_empty() {
-                    continue;
-                }
-                line.text = line.text.trim().to_string();
-                texts.push(line.clone());
-                line = Default::default();
-                continue;
+        let text = word.text.trim();
+        match regions.iter_mut().find(|region| region.key == key) {
+            Some(region) => {
+                region
+                    .text
+                    .push(if region.line == line { ' ' } else { '\n' });
+                region.text.push_str(text);
+                region.line = line;
+                region.left = region.left.min(word.left);
+                region.top = region.top.min(word.top);
+                region.right = region.right.max(word.left + word.width);
+                region.bottom = region.bottom.max(word.top + word.height);
+                region.confidence += word.conf;
+                region.words += 1;
             }
-            if line.text.trim().is_empty() {
-                line.x = dt.left;
-                line.y = dt.top;
-            }
-            if line.height < dt.height {
-                line.height = dt.height;
-            }
-            line.width += dt.width;
-            line.text.push_str(&format!("{} ", dt.text));
+            None => regions.push(Region {
+                key,
+                line,
+                left: word.left,
+                top: word.top,
+                right: word.left + word.width,
+                bottom: word.top + word.height,
+                text: text.to_string(),
+                confidence: word.conf,
+                words: 1,
+            }),
         }
-        Ok(texts)
     }
+    regions
+        .into_iter()
+        .map(|region| AreaData {
+            x: region.left,
+            y: region.top,
+            width: region.right - region.left,
+            height: region.bottom - region.top,
+            text: region.text,
+            confidence: region.confidence / region.words as f32,
+            ..Default::default()
+        })
+        .collect()
+}
 
-    pub fn ocr_image(&self, path: &str) -> Result<String> {
-        let default_args = rusty_tesseract::Args {
-            lang: self.code.to_owned(),
-            ..rusty_tesseract::Args::default()
-        };
-        let image = Image::from_path(path)?;
-        let text = rusty_tesseract::image_to_string(&image, &default_args)?;
-        Ok(text)
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_ocr_language_has_a_translation_source() {
+        let sources = TranslatorData::all_languages().map(|language| language.code);
+        for (code, _) in LANGUAGES {
+            let source = OcrData::new(code).to_translator().code;
+            assert!(
+                source == "auto" || sources.contains(&source),
+                "{code} maps to unknown source {source}"
+            );
+        }
+    }
+
+    fn word(line_num: i32, left: i32, conf: f32, text: &str) -> Data {
+        Data {
+            level: 5,
+            page_num: 1,
+            block_num: 1,
+            par_num: 1,
+            line_num,
+            word_num: 1,
+            left,
+            top: line_num * 20,
+            width: 10,
+            height: 10,
+            conf,
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn regions_keep_their_low_confidence_words() {
+        let data = [
+            word(1, 0, 90.0, "Hello"),
+            word(1, 20, 10.0, "w0rld"),
+            word(2, 0, 20.0, "~~"),
+        ];
+        let lines = group_words(&data, false);
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].text, "Hello w0rld");
+        assert_eq!(lines[0].confidence, 50.0);
+        assert_eq!(lines[1].confidence, 20.0);
+
+        let blocks = group_words(&data, true);
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].text, "Hello w0rld\n~~");
+        assert_eq!(blocks[0].confidence, 40.0);
+    }
+
+    #[test]
+    fn vertical_languages_keep_their_source() {
+        assert_eq!(OcrData::new("jpn_vert").to_translator().code, "ja");
+        assert_eq!(OcrData::new("kor_vert").to_translator().code, "ko");
+        assert_eq!(OcrData::new("chi_tra").to_translator().code, "zh");
+        assert_eq!(OcrData::new("chi_sim_vert").to_translator().code, "zh");
+        assert!(OcrData::new("chi_tra_vert").is_vertical);
+        assert_eq!(OcrData::new("ara").to_translator().code, "auto");
     }
 }
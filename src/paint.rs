@@ -16,6 +16,9 @@ pub fn draw_fitted_text_with_background(cr: &Context, area: &AreaData) -> Result
 
     draw_rectangle(cr, &rect)?;
     draw_text(cr, &area.text, &rect, Some(font_family))?;
+    if !area.provider.is_empty() {
+        draw_caption(cr, &area.provider, &rect)?;
+    }
 
     Ok(())
 }
@@ -67,6 +70,50 @@ pub fn draw_text(
     Ok(())
 }
 
+/// Small label in the bottom right corner of `rect`.
+pub fn draw_caption(cr: &Context, text: &str, rect: &gtk::gdk::Rectangle) -> Result<()> {
+    let layout = create_layout(cr);
+    layout.set_font_description(Some(&pango::FontDescription::from_string("Sans 7")));
+    layout.set_text(text);
+    let (text_width, text_height) = layout.pixel_size();
+
+    cr.save()?;
+    cr.set_source_rgba(1.0, 1.0, 1.0, 0.6);
+    cr.move_to(
+        (rect.x() + rect.width() - text_width - 2) as f64,
+        (rect.y() + rect.height() - text_height) as f64,
+    );
+    pangocairo::functions::show_layout(cr, &layout);
+    cr.restore()?;
+
+    Ok(())
+}
+
+/// Dashed outline and confidence of an area left untranslated.
+pub fn draw_uncertain(cr: &Context, area: &AreaData) -> Result<()> {
+    if area.text.trim().is_empty() {
+        return Ok(());
+    }
+    let rect = gtk::gdk::Rectangle::new(area.x, area.y, area.width, area.height);
+
+    cr.save()?;
+    cr.rectangle(
+        rect.x() as f64,
+        rect.y() as f64,
+        rect.width() as f64,
+        rect.height() as f64,
+    );
+    cr.set_source_rgba(0.0, 0.0, 0.0, 0.25);
+    cr.fill_preserve()?;
+    cr.set_source_rgba(1.0, 1.0, 1.0, 0.5);
+    cr.set_line_width(1.0);
+    cr.set_dash(&[4.0, 4.0], 0.0);
+    cr.stroke()?;
+    cr.restore()?;
+
+    draw_caption(cr, &format!("? {:.0}%", area.confidence), &rect)
+}
+
 pub fn draw_rectangle(cr: &Context, rect: &gtk::gdk::Rectangle) -> Result<()> {
     cr.save()?;
     cr.rectangle(
@@ -0,0 +1,174 @@
+use image::{imageops, DynamicImage, GrayImage, ImageBuffer, Pixel, RgbaImage};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Binarize {
+    #[default]
+    Off,
+    /// Pixels brighter than [`Preprocess::threshold`] turn white.
+    Threshold,
+    /// Picks the threshold from the image histogram.
+    Otsu,
+}
+
+impl Binarize {
+    pub const ALL: [Binarize; 3] = [Binarize::Off, Binarize::Threshold, Binarize::Otsu];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Binarize::Off => "Off",
+            Binarize::Threshold => "Threshold",
+            Binarize::Otsu => "Otsu",
+        }
+    }
+}
+
+/// Filters applied to a captured area before Tesseract reads it.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Preprocess {
+    pub grayscale: bool,
+    /// Tesseract reads small game fonts better at 2 to 4 times their size.
+    pub scale: u32,
+    pub binarize: Binarize,
+    pub threshold: u8,
+    /// Tesseract expects dark text on a light background.
+    pub invert: bool,
+    pub sharpen: bool,
+    /// Median filter removing isolated pixels from busy backgrounds.
+    pub denoise: bool,
+}
+
+impl Default for Preprocess {
+    fn default() -> Self {
+        Self {
+            grayscale: false,
+            scale: 1,
+            binarize: Binarize::Off,
+            threshold: 128,
+            invert: false,
+            sharpen: false,
+            denoise: false,
+        }
+    }
+}
+
+impl Preprocess {
+    pub const MAX_SCALE: u32 = 4;
+
+    pub fn apply(&self, image: RgbaImage) -> DynamicImage {
+        if *self == Self::default() {
+            return DynamicImage::ImageRgba8(image);
+        }
+        if !self.grayscale && self.binarize == Binarize::Off {
+            let mut image = self.filter(image);
+            if self.invert {
+                imageops::invert(&mut image);
+            }
+            return DynamicImage::ImageRgba8(image);
+        }
+        let mut image = self.filter(DynamicImage::ImageRgba8(image).to_luma8());
+        let threshold = match self.binarize {
+            Binarize::Off => None,
+            Binarize::Threshold => Some(self.threshold),
+            Binarize::Otsu => Some(otsu_threshold(&image)),
+        };
+        if let Some(threshold) = threshold {
+            for pixel in image.pixels_mut() {
+                pixel.0[0] = if pixel.0[0] > threshold { 255 } else { 0 };
+            }
+        }
+        if self.invert {
+            imageops::invert(&mut image);
+        }
+        DynamicImage::ImageLuma8(image)
+    }
+
+    /// Steps shared by color and grayscale images, in the order they run.
+    fn filter<P>(&self, image: ImageBuffer<P, Vec<u8>>) -> ImageBuffer<P, Vec<u8>>
+    where
+        P: Pixel<Subpixel = u8> + 'static,
+    {
+        let scale = self.scale.clamp(1, Self::MAX_SCALE);
+        let mut image = if scale > 1 {
+            let (width, height) = image.dimensions();
+            imageops::resize(
+                &image,
+                width * scale,
+                height * scale,
+                imageops::FilterType::CatmullRom,
+            )
+        } else {
+            image
+        };
+        if self.denoise {
+            image = median(&image);
+        }
+        if self.sharpen {
+            image = imageops::unsharpen(&image, 1.0, 5);
+        }
+        image
+    }
+}
+
+/// 3x3 median of every channel.
+fn median<P>(image: &ImageBuffer<P, Vec<u8>>) -> ImageBuffer<P, Vec<u8>>
+where
+    P: Pixel<Subpixel = u8>,
+{
+    let (width, height) = image.dimensions();
+    let mut output = image.clone();
+    let mut window = Vec::with_capacity(9);
+    for y in 0..height {
+        for x in 0..width {
+            let pixel = output.get_pixel_mut(x, y).channels_mut();
+            for (channel, value) in pixel.iter_mut().enumerate() {
+                window.clear();
+                for ny in y.saturating_sub(1)..=(y + 1).min(height - 1) {
+                    for nx in x.saturating_sub(1)..=(x + 1).min(width - 1) {
+                        window.push(image.get_pixel(nx, ny).channels()[channel]);
+                    }
+                }
+                window.sort_unstable();
+                *value = window[window.len() / 2];
+            }
+        }
+    }
+    output
+}
+
+/// Threshold maximizing the variance between the dark and light pixels.
+fn otsu_threshold(image: &GrayImage) -> u8 {
+    let mut histogram = [0u64; 256];
+    for pixel in image.pixels() {
+        histogram[pixel.0[0] as usize] += 1;
+    }
+    let total = image.pixels().len() as f64;
+    let sum = histogram
+        .iter()
+        .enumerate()
+        .map(|(value, count)| value as f64 * *count as f64)
+        .sum::<f64>();
+
+    let mut best = (0, 0.0);
+    let mut background = 0.0;
+    let mut background_sum = 0.0;
+    for (value, count) in histogram.iter().enumerate() {
+        background += *count as f64;
+        if background == 0.0 {
+            continue;
+        }
+        let foreground = total - background;
+        if foreground == 0.0 {
+            break;
+        }
+        background_sum += value as f64 * *count as f64;
+        let background_mean = background_sum / background;
+        let foreground_mean = (sum - background_sum) / foreground;
+        let variance = background * foreground * (background_mean - foreground_mean).powi(2);
+        if variance > best.1 {
+            best = (value, variance);
+        }
+    }
+    best.0 as u8
+}
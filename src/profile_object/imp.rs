@@ -6,6 +6,8 @@ use glib::Properties;
 use gtk::{gio, glib};
 use std::cell::OnceCell;
 
+use crate::{cleanup::CleanupOptions, glossary::GlossaryEntry, rewrite::RewriteRule};
+
 #[derive(Properties, Default)]
 #[properties(wrapper_type = super::ProfileObject)]
 pub struct ProfileObject {
@@ -22,7 +24,22 @@ pub struct ProfileObject {
     #[property(get, set)]
     pub use_areas: RefCell<bool>,
     #[property(get, set)]
+    pub ollama_model: RefCell<String>,
+    #[property(get, set)]
+    pub ollama_temperature: RefCell<f64>,
+    #[property(get, set)]
+    pub ollama_keep_alive: RefCell<String>,
+    #[property(get, set)]
+    pub context_lines: RefCell<u32>,
+    #[property(get, set)]
+    pub paragraphs: RefCell<bool>,
+    #[property(get, set)]
+    pub min_confidence: RefCell<u32>,
+    #[property(get, set)]
     pub areas: OnceCell<gio::ListStore>,
+    pub glossary: RefCell<Vec<GlossaryEntry>>,
+    pub rewrite_rules: RefCell<Vec<RewriteRule>>,
+    pub cleanup: RefCell<CleanupOptions>,
 }
 
 #[glib::object_subclass]
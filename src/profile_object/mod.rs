@@ -7,6 +7,9 @@ use gtk::{gio, glib};
 use serde::{Deserialize, Serialize};
 
 use crate::area_object::{AreaData, AreaObject};
+use crate::cleanup::CleanupOptions;
+use crate::glossary::GlossaryEntry;
+use crate::rewrite::RewriteRule;
 
 glib::wrapper! {
     pub struct ProfileObject(ObjectSubclass<imp::ProfileObject>);
@@ -41,6 +44,15 @@ impl ProfileObject {
             language: self.imp().language.borrow().clone(),
             translation: self.imp().translation.borrow().clone(),
             use_areas: *self.imp().use_areas.borrow(),
+            ollama_model: self.imp().ollama_model.borrow().clone(),
+            ollama_temperature: *self.imp().ollama_temperature.borrow(),
+            ollama_keep_alive: self.imp().ollama_keep_alive.borrow().clone(),
+            glossary: self.glossary(),
+            rewrite_rules: self.rewrite_rules(),
+            cleanup: self.cleanup(),
+            context_lines: *self.imp().context_lines.borrow(),
+            paragraphs: *self.imp().paragraphs.borrow(),
+            min_confidence: *self.imp().min_confidence.borrow(),
             areas: self
                 .areas()
                 .iter::<AreaObject>()
@@ -66,7 +78,7 @@ impl ProfileObject {
         let areas = gio::ListStore::new::<AreaObject>();
         areas.extend_from_slice(&areas_to_extend);
 
-        Self::new(
+        let profile = Self::new(
             &title,
             &app_name,
             &app_title,
@@ -74,7 +86,41 @@ impl ProfileObject {
             &translation,
             areas,
             use_areas,
-        )
+        );
+        profile.set_ollama_model(profile_data.ollama_model);
+        profile.set_ollama_temperature(profile_data.ollama_temperature);
+        profile.set_ollama_keep_alive(profile_data.ollama_keep_alive);
+        profile.set_glossary(profile_data.glossary);
+        profile.set_rewrite_rules(profile_data.rewrite_rules);
+        profile.set_cleanup(profile_data.cleanup);
+        profile.set_context_lines(profile_data.context_lines);
+        profile.set_paragraphs(profile_data.paragraphs);
+        profile.set_min_confidence(profile_data.min_confidence);
+        profile
+    }
+
+    pub fn glossary(&self) -> Vec<GlossaryEntry> {
+        self.imp().glossary.borrow().clone()
+    }
+
+    pub fn set_glossary(&self, glossary: Vec<GlossaryEntry>) {
+        self.imp().glossary.replace(glossary);
+    }
+
+    pub fn rewrite_rules(&self) -> Vec<RewriteRule> {
+        self.imp().rewrite_rules.borrow().clone()
+    }
+
+    pub fn set_rewrite_rules(&self, rules: Vec<RewriteRule>) {
+        self.imp().rewrite_rules.replace(rules);
+    }
+
+    pub fn cleanup(&self) -> CleanupOptions {
+        *self.imp().cleanup.borrow()
+    }
+
+    pub fn set_cleanup(&self, cleanup: CleanupOptions) {
+        self.imp().cleanup.replace(cleanup);
     }
 }
 
@@ -87,4 +133,28 @@ pub struct ProfileData {
     pub translation: String,
     pub use_areas: bool,
     pub areas: Vec<AreaData>,
+    #[serde(default)]
+    pub ollama_model: String,
+    #[serde(default)]
+    pub ollama_temperature: f64,
+    #[serde(default)]
+    pub ollama_keep_alive: String,
+    #[serde(default)]
+    pub glossary: Vec<GlossaryEntry>,
+    /// Applied in order to the translated text.
+    #[serde(default)]
+    pub rewrite_rules: Vec<RewriteRule>,
+    /// Applied to the OCR text before it is translated.
+    #[serde(default)]
+    pub cleanup: CleanupOptions,
+    /// Previous lines of each area given to providers that use context.
+    #[serde(default)]
+    pub context_lines: u32,
+    /// Whole window mode merges the lines of a text block into one area.
+    #[serde(default)]
+    pub paragraphs: bool,
+    /// Areas, or whole window regions, read with a lower mean confidence are
+    /// outlined with their confidence instead of translated.
+    #[serde(default)]
+    pub min_confidence: u32,
 }
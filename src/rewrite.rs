@@ -0,0 +1,40 @@
+use anyhow::{Context, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// Find and replace applied to the translated text.
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RewriteRule {
+    pub pattern: String,
+    /// May reference the pattern groups, like `$1` or `${name}`.
+    pub replacement: String,
+}
+
+/// Rules compiled once and applied in order, each one to the result of the
+/// previous.
+pub struct Rewriter {
+    rules: Vec<(Regex, String)>,
+}
+
+impl Rewriter {
+    pub fn new(rules: &[RewriteRule]) -> Result<Self> {
+        let rules = rules
+            .iter()
+            .filter(|rule| !rule.pattern.is_empty())
+            .map(|rule| {
+                let regex = Regex::new(&rule.pattern)
+                    .with_context(|| format!("Invalid rewrite rule \"{}\"", rule.pattern))?;
+                Ok((regex, rule.replacement.clone()))
+            })
+            .collect::<Result<Vec<(Regex, String)>>>()?;
+        Ok(Self { rules })
+    }
+
+    pub fn apply(&self, text: &str) -> String {
+        let mut text = text.to_string();
+        for (regex, replacement) in &self.rules {
+            text = regex.replace_all(&text, replacement.as_str()).into_owned();
+        }
+        text
+    }
+}
@@ -1,12 +1,11 @@
 mod imp;
 
-use crate::{area_object::AreaData, utils};
+use crate::area_object::AreaData;
 use anyhow::Result;
 use glib::Object;
 use gtk::glib;
-use image::{GenericImage, GenericImageView, ImageBuffer, Rgba};
+use image::{GenericImage, GenericImageView, ImageBuffer, Rgba, RgbaImage};
 use std::cmp;
-use uuid::Uuid;
 use xcap::Window;
 
 glib::wrapper! {
@@ -38,28 +37,17 @@ impl ScreenData {
         }
     }
 
-    pub fn capture_areas(&self, areas: &Vec<AreaData>) -> Result<Vec<String>> {
-        let mut image = self.capture_screen()?;
-        let path = utils::temp_path()?;
-        let mut strings = vec![];
+    pub fn capture_areas(&self, areas: &Vec<AreaData>) -> Result<Vec<RgbaImage>> {
+        let mut image = self.capture()?;
+        let mut images = vec![];
         for a in areas {
             let copy = image.sub_image(a.x as u32, a.y as u32, a.width as u32, a.height as u32);
-            let img_path = format!("{}/{}.png", &path, Uuid::new_v4());
-            copy.to_image().save(&img_path)?;
-            strings.push(img_path);
+            images.push(copy.to_image());
         }
-        Ok(strings)
+        Ok(images)
     }
 
-    pub fn capture(&self) -> Result<String> {
-        let image = self.capture_screen()?;
-        let path = utils::temp_path()?;
-        let path = format!("{}/{}.png", path, Uuid::new_v4());
-        image.save(&path)?;
-        Ok(path)
-    }
-
-    fn capture_screen(&self) -> Result<ImageBuffer<Rgba<u8>, Vec<u8>>> {
+    pub fn capture(&self) -> Result<RgbaImage> {
         let windows = Window::all()?;
         let window = windows.iter().find(|w| {
             if let Ok(id) = w.id() {
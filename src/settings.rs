@@ -1,13 +1,43 @@
 use serde::{Deserialize, Serialize};
 
-use crate::utils;
-use std::fs;
+use crate::{
+    translation::{limiter::Limits, openai, webdriver::Backend},
+    utils,
+};
+use std::{collections::HashMap, fs};
+
+/// Attempts made after the first failure when a provider has no setting.
+const DEFAULT_RETRIES: u32 = 2;
+
+/// Seconds a provider request may take when the provider has no setting.
+const DEFAULT_TIMEOUT: u64 = 30;
 
 #[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Settings {
     pub ocr_lang: String,
     pub tra_lang: String,
     pub tra_provider: String,
+    pub tra_fallback: Vec<String>,
+    pub retries: HashMap<String, u32>,
+    pub retry_delay: u64,
+    pub timeouts: HashMap<String, u64>,
+    pub rate_limits: HashMap<String, f64>,
+    pub quotas: HashMap<String, u64>,
+    pub webdriver_backend: String,
+    pub webdriver_path: String,
+    pub browser_path: String,
+    pub libre_url: String,
+    pub libre_key: String,
+    pub deepl_key: String,
+    pub deepl_formality: String,
+    pub openai_url: String,
+    pub openai_key: String,
+    pub openai_model: String,
+    pub llm_prompt: String,
+    pub ollama_url: String,
+    pub command_path: String,
+    pub command_args: String,
 }
 
 impl Settings {
@@ -27,6 +57,128 @@ impl Settings {
         }
     }
 
+    /// Providers tried in order: the selected one followed by the fallbacks.
+    pub fn tra_chain(&self) -> Vec<&str> {
+        let mut chain = vec![self.tra_provider()];
+        for id in &self.tra_fallback {
+            if !chain.contains(&id.as_str()) {
+                chain.push(id);
+            }
+        }
+        chain
+    }
+
+    pub fn tra_fallback(&self) -> &[String] {
+        &self.tra_fallback
+    }
+
+    pub fn retries(&self, provider: &str) -> u32 {
+        self.retries
+            .get(provider)
+            .copied()
+            .unwrap_or(DEFAULT_RETRIES)
+    }
+
+    pub fn timeout(&self, provider: &str) -> u64 {
+        self.timeouts
+            .get(provider)
+            .copied()
+            .filter(|timeout| *timeout > 0)
+            .unwrap_or(DEFAULT_TIMEOUT)
+    }
+
+    /// Milliseconds before the first retry, doubled on each following one.
+    pub fn retry_delay(&self) -> u64 {
+        if self.retry_delay == 0 {
+            500
+        } else {
+            self.retry_delay
+        }
+    }
+
+    pub fn limits(&self, provider: &str) -> Limits {
+        Limits {
+            requests_per_second: self.rate_limits.get(provider).copied().unwrap_or_default(),
+            monthly_characters: self.quotas.get(provider).copied().unwrap_or_default(),
+        }
+    }
+
+    pub fn webdriver_backend(&self) -> Backend {
+        Backend::from_id(&self.webdriver_backend)
+    }
+
+    pub fn webdriver_path(&self) -> &str {
+        &self.webdriver_path
+    }
+
+    pub fn browser_path(&self) -> &str {
+        &self.browser_path
+    }
+
+    pub fn libre_url(&self) -> &str {
+        if self.libre_url.is_empty() {
+            "http://localhost:5000"
+        } else {
+            &self.libre_url
+        }
+    }
+
+    pub fn libre_key(&self) -> &str {
+        &self.libre_key
+    }
+
+    pub fn deepl_key(&self) -> &str {
+        &self.deepl_key
+    }
+
+    pub fn deepl_formality(&self) -> &str {
+        if self.deepl_formality.is_empty() {
+            "default"
+        } else {
+            &self.deepl_formality
+        }
+    }
+
+    pub fn openai_url(&self) -> &str {
+        if self.openai_url.is_empty() {
+            "http://localhost:8080/v1"
+        } else {
+            &self.openai_url
+        }
+    }
+
+    pub fn openai_key(&self) -> &str {
+        &self.openai_key
+    }
+
+    pub fn openai_model(&self) -> &str {
+        &self.openai_model
+    }
+
+    pub fn llm_prompt(&self) -> &str {
+        if self.llm_prompt.trim().is_empty() {
+            openai::DEFAULT_PROMPT
+        } else {
+            &self.llm_prompt
+        }
+    }
+
+    pub fn ollama_url(&self) -> &str {
+        if self.ollama_url.is_empty() {
+            "http://localhost:11434"
+        } else {
+            &self.ollama_url
+        }
+    }
+
+    pub fn command_path(&self) -> &str {
+        &self.command_path
+    }
+
+    pub fn command_args(&self) -> &str {
+        &self.command_args
+    }
+
     pub fn set(&mut self, prop: &str, value: String) -> Result<(), anyhow::Error> {
         match prop {
             "tra-lang" => {
@@ -35,9 +187,85 @@ impl Settings {
             "tra-provider" => {
                 self.tra_provider = value;
             }
+            "tra-fallback" => {
+                self.tra_fallback = value
+                    .split(',')
+                    .map(|id| id.trim().to_string())
+                    .filter(|id| !id.is_empty())
+                    .collect();
+            }
+            "retry-delay" => {
+                self.retry_delay = value.parse().unwrap_or_default();
+            }
+            "webdriver-backend" => {
+                self.webdriver_backend = value;
+            }
+            "webdriver-path" => {
+                self.webdriver_path = value;
+            }
+            "browser-path" => {
+                self.browser_path = value;
+            }
             "ocr-lang" => {
                 self.ocr_lang = value;
             }
+            "libre-url" => {
+                self.libre_url = value;
+            }
+            "libre-key" => {
+                self.libre_key = value;
+            }
+            "deepl-key" => {
+                self.deepl_key = value;
+            }
+            "deepl-formality" => {
+                self.deepl_formality = value;
+            }
+            "openai-url" => {
+                self.openai_url = value;
+            }
+            "openai-key" => {
+                self.openai_key = value;
+            }
+            "openai-model" => {
+                self.openai_model = value;
+            }
+            "llm-prompt" => {
+                self.llm_prompt = value;
+            }
+            "ollama-url" => {
+                self.ollama_url = value;
+            }
+            "command-path" => {
+                self.command_path = value;
+            }
+            "command-args" => {
+                self.command_args = value;
+            }
+            prop if prop.ends_with("-retries") => {
+                if let Ok(retries) = value.parse() {
+                    let id = prop.trim_end_matches("-retries").to_string();
+                    self.retries.insert(id, retries);
+                }
+            }
+            prop if prop.ends_with("-timeout") => {
+                if let Ok(timeout) = value.parse() {
+                    let id = prop.trim_end_matches("-timeout").to_string();
+                    self.timeouts.insert(id, timeout);
+                }
+            }
+            prop if prop.ends_with("-rate") => {
+                if let Ok(rate) = value.parse() {
+                    let id = prop.trim_end_matches("-rate").to_string();
+                    self.rate_limits.insert(id, rate);
+                }
+            }
+            prop if prop.ends_with("-quota") => {
+                if let Ok(quota) = value.parse() {
+                    let id = prop.trim_end_matches("-quota").to_string();
+                    self.quotas.insert(id, quota);
+                }
+            }
             &_ => {}
         }
         if let Err(err) = self.update_json() {
@@ -0,0 +1,98 @@
+use anyhow::{Context, Result};
+use image::{
+    codecs::png::{CompressionType, FilterType, PngEncoder},
+    DynamicImage,
+};
+use rusty_tesseract::{Args, Data};
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::thread;
+
+/// Words of `image` with their boxes and confidence, read by Tesseract from
+/// its standard input.
+pub fn image_to_data(image: &DynamicImage, args: &Args) -> Result<Vec<Data>> {
+    let output = run(image, args)?;
+    Ok(output.lines().skip(1).filter_map(parse_data).collect())
+}
+
+/// Pipes the image as a quickly encoded PNG instead of writing it to a file.
+fn run(image: &DynamicImage, args: &Args) -> Result<String> {
+    let mut png = Vec::new();
+    image.write_with_encoder(PngEncoder::new_with_quality(
+        &mut png,
+        CompressionType::Fast,
+        FilterType::NoFilter,
+    ))?;
+
+    let mut command = Command::new(if cfg!(windows) {
+        "tesseract.exe"
+    } else {
+        "tesseract"
+    });
+    command.args(["stdin", "stdout", "-l", &args.lang]);
+    if let Some(dpi) = args.dpi {
+        command.arg("--dpi").arg(dpi.to_string());
+    }
+    if let Some(psm) = args.psm {
+        command.arg("--psm").arg(psm.to_string());
+    }
+    if let Some(oem) = args.oem {
+        command.arg("--oem").arg(oem.to_string());
+    }
+    for (key, value) in &args.config_variables {
+        command.arg("-c").arg(format!("{key}={value}"));
+    }
+    command.arg("tsv");
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        // CREATE_NO_WINDOW, the app has no console to reuse.
+        command.creation_flags(0x0800_0000);
+    }
+
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("Tesseract not found, is it installed and in the PATH?")?;
+    let mut stdin = child
+        .stdin
+        .take()
+        .context("Failed to open Tesseract input")?;
+    // Written from another thread so a full output pipe can't block the input.
+    let writer = thread::spawn(move || stdin.write_all(&png));
+    let output = child.wait_with_output()?;
+    let _ = writer.join();
+
+    if !output.status.success() {
+        return Err(anyhow::anyhow!(
+            "Tesseract failed ({}): {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+fn parse_data(line: &str) -> Option<Data> {
+    let mut columns = line.split('\t');
+    let mut next = || columns.next()?.trim().parse::<i32>().ok();
+    let (level, page_num, block_num, par_num, line_num, word_num) =
+        (next()?, next()?, next()?, next()?, next()?, next()?);
+    let (left, top, width, height) = (next()?, next()?, next()?, next()?);
+    Some(Data {
+        level,
+        page_num,
+        block_num,
+        par_num,
+        line_num,
+        word_num,
+        left,
+        top,
+        width,
+        height,
+        conf: columns.next()?.trim().parse().ok()?,
+        text: columns.next().unwrap_or_default().to_string(),
+    })
+}
@@ -0,0 +1,194 @@
+use anyhow::Result;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use super::{Line, Translator};
+use crate::utils;
+
+/// Entries kept in memory and on disk, the least recently used go first.
+const CAPACITY: usize = 5000;
+
+static CACHE: Lazy<Mutex<Cache>> = Lazy::new(|| Mutex::new(Cache::load()));
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Key {
+    pub provider: String,
+    /// Hash of the provider options the translation was made with.
+    #[serde(default)]
+    pub config: String,
+    pub source: String,
+    pub target: String,
+    pub text: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Entry {
+    #[serde(flatten)]
+    pub key: Key,
+    pub translation: String,
+}
+
+#[derive(Default)]
+struct Cache {
+    entries: HashMap<Key, (String, u64)>,
+    clock: u64,
+    dirty: bool,
+}
+
+impl Key {
+    pub fn new(translator: &dyn Translator, source: &str, target: &str, text: &str) -> Self {
+        Key {
+            provider: translator.id().to_string(),
+            config: fingerprint(&translator.fingerprint()),
+            source: source.to_string(),
+            target: target.to_string(),
+            text: text.trim().to_string(),
+        }
+    }
+}
+
+impl Cache {
+    fn load() -> Self {
+        let mut cache = Cache::default();
+        let entries = utils::cache_path()
+            .and_then(utils::open_file)
+            .and_then(|file| Ok(serde_json::from_reader::<_, Vec<Entry>>(file)?));
+        if let Ok(entries) = entries {
+            // Stored from the most to the least recently used.
+            for entry in entries.into_iter().rev() {
+                cache.insert(entry.key, entry.translation);
+            }
+            cache.dirty = false;
+        }
+        cache
+    }
+
+    fn get(&mut self, key: &Key) -> Option<String> {
+        self.clock += 1;
+        let clock = self.clock;
+        self.entries.get_mut(key).map(|(translation, used)| {
+            *used = clock;
+            translation.clone()
+        })
+    }
+
+    fn insert(&mut self, key: Key, translation: String) {
+        if self.entries.len() >= CAPACITY && !self.entries.contains_key(&key) {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, (_, used))| *used)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.clock += 1;
+        self.entries.insert(key, (translation, self.clock));
+        self.dirty = true;
+    }
+
+    fn entries(&self) -> Vec<Entry> {
+        let mut entries = self.entries.iter().collect::<Vec<_>>();
+        entries.sort_by_key(|(_, (_, used))| std::cmp::Reverse(*used));
+        entries
+            .into_iter()
+            .map(|(key, (translation, _))| Entry {
+                key: key.clone(),
+                translation: translation.clone(),
+            })
+            .collect()
+    }
+}
+
+/// FNV-1a, stable across builds unlike the standard hasher.
+fn fingerprint(options: &str) -> String {
+    if options.is_empty() {
+        return String::new();
+    }
+    let hash = options.bytes().fold(0xcbf29ce484222325_u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    });
+    format!("{hash:016x}")
+}
+
+fn cache() -> MutexGuard<'static, Cache> {
+    CACHE.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Cached entries, the most recently used first.
+pub fn entries() -> Vec<Entry> {
+    cache().entries()
+}
+
+pub fn clear() -> Result<()> {
+    let mut cache = cache();
+    cache.entries.clear();
+    cache.dirty = true;
+    drop(cache);
+    save()
+}
+
+/// Writes the cache to disk when it changed since the last save.
+pub fn save() -> Result<()> {
+    let mut cache = cache();
+    if !cache.dirty {
+        return Ok(());
+    }
+    let file = fs::File::create(utils::cache_path()?)?;
+    serde_json::to_writer(file, &cache.entries())?;
+    cache.dirty = false;
+    Ok(())
+}
+
+/// Translates `texts` with `translator`, only requesting the ones not cached yet.
+pub async fn translate(
+    translator: &dyn Translator,
+    texts: &[String],
+    context: &[Vec<Line>],
+    source: &str,
+    target: &str,
+) -> Result<Vec<String>> {
+    let keys = texts
+        .iter()
+        .map(|text| Key::new(translator, source, target, text))
+        .collect::<Vec<Key>>();
+    let mut translated = keys
+        .iter()
+        .map(|key| {
+            if key.text.is_empty() {
+                Some(String::new())
+            } else {
+                cache().get(key)
+            }
+        })
+        .collect::<Vec<Option<String>>>();
+
+    let missing = (0..keys.len())
+        .filter(|i| translated[*i].is_none())
+        .collect::<Vec<usize>>();
+    if !missing.is_empty() {
+        let pending = missing
+            .iter()
+            .map(|i| keys[*i].text.clone())
+            .collect::<Vec<String>>();
+        let pending_context = missing
+            .iter()
+            .map(|i| context.get(*i).cloned().unwrap_or_default())
+            .collect::<Vec<Vec<Line>>>();
+        let results =
+            super::translate_all(translator, &pending, &pending_context, source, target).await?;
+        for (i, translation) in missing.into_iter().zip(results) {
+            cache().insert(keys[i].clone(), translation.clone());
+            translated[i] = Some(translation);
+        }
+    }
+
+    Ok(translated
+        .into_iter()
+        .map(Option::unwrap_or_default)
+        .collect())
+}
@@ -0,0 +1,142 @@
+use anyhow::{anyhow, Error, Result};
+use fantoccini::error::CmdError;
+use tokio::time::{sleep, timeout, Duration};
+
+use super::{
+    cache,
+    limiter::{Limited, QuotaExceeded},
+    webdriver, Google, Line, TimedOut, Translator,
+};
+use crate::{profile_object::ProfileData, settings::Settings};
+
+/// Longest wait between two attempts, no matter how many retries are configured.
+const MAX_BACKOFF: u64 = 30_000;
+
+/// Providers tried in order until one of them translates the texts.
+pub struct Chain {
+    links: Vec<Link>,
+    delay: u64,
+}
+
+struct Link {
+    translator: Box<dyn Translator>,
+    retries: u32,
+    timeout: u64,
+}
+
+impl Chain {
+    /// Builds the selected provider followed by the configured fallbacks.
+    pub fn new(settings: &Settings, profile: &ProfileData) -> Self {
+        let mut registry = super::registry(settings);
+        let mut links = vec![];
+        for id in settings.tra_chain() {
+            let Some(index) = registry.iter().position(|translator| translator.id() == id) else {
+                continue;
+            };
+            let mut translator = registry.swap_remove(index);
+            translator.configure(profile);
+            links.push(Link {
+                translator: Box::new(Limited::new(translator, settings.limits(id))),
+                retries: settings.retries(id),
+                timeout: settings.timeout(id),
+            });
+        }
+        if links.is_empty() {
+            links.push(Link {
+                translator: Box::new(Limited::new(
+                    Box::new(Google::new(settings)),
+                    settings.limits("google"),
+                )),
+                retries: settings.retries("google"),
+                timeout: settings.timeout("google"),
+            });
+        }
+        Self {
+            links,
+            delay: settings.retry_delay(),
+        }
+    }
+
+    /// Translates the texts with the first provider that succeeds.
+    ///
+    /// Each provider is retried with exponential backoff before moving on to
+    /// the next one. Returns the translations and the name of the provider
+    /// that produced them. When every provider only timed out the error is a
+    /// [`TimedOut`], so the caller can skip the texts instead of giving up.
+    pub async fn translate(
+        &self,
+        texts: &[String],
+        context: &[Vec<Line>],
+        source: &str,
+        target: &str,
+    ) -> Result<(Vec<String>, &'static str)> {
+        let mut errors = vec![];
+        let mut timed_out: Option<Error> = None;
+        let mut only_timeouts = true;
+        for link in &self.links {
+            let name = link.translator.name();
+            let mut last_error: Option<Error> = None;
+            for attempt in 0..=link.retries {
+                if attempt > 0 {
+                    let backoff = self.delay.saturating_mul(1 << (attempt - 1).min(16));
+                    sleep(Duration::from_millis(backoff.min(MAX_BACKOFF))).await;
+                }
+                match link.translate(texts, context, source, target).await {
+                    Ok(translated) => return Ok((translated, name)),
+                    Err(err) if err.is::<QuotaExceeded>() => {
+                        last_error = Some(err);
+                        break;
+                    }
+                    Err(err) => last_error = Some(err),
+                }
+            }
+            if let Some(err) = last_error {
+                errors.push(format!("{name}: {err}"));
+                if err.is::<TimedOut>() {
+                    timed_out.get_or_insert(err);
+                } else {
+                    only_timeouts = false;
+                }
+            }
+        }
+        let summary = format!("Every translation provider failed\n{}", errors.join("\n"));
+        match timed_out {
+            Some(err) if only_timeouts => Err(err.context(summary)),
+            _ => Err(anyhow!(summary)),
+        }
+    }
+
+    pub async fn close(&self) {
+        for link in &self.links {
+            link.translator.close().await;
+        }
+    }
+}
+
+impl Link {
+    /// One attempt, abandoned after the provider's timeout.
+    async fn translate(
+        &self,
+        texts: &[String],
+        context: &[Vec<Line>],
+        source: &str,
+        target: &str,
+    ) -> Result<Vec<String>> {
+        let request = cache::translate(self.translator.as_ref(), texts, context, source, target);
+        let seconds = match timeout(Duration::from_secs(self.timeout), request).await {
+            // A page that never shows the translation times out like a slow request.
+            Ok(Err(err)) if matches!(err.downcast_ref(), Some(CmdError::WaitTimeout)) => {
+                webdriver::WAIT_TIMEOUT.as_secs()
+            }
+            Ok(result) => return result,
+            Err(_) => self.timeout,
+        };
+        // Whatever the provider was waiting for is left half done.
+        self.translator.close().await;
+        Err(TimedOut {
+            provider: self.translator.name(),
+            seconds,
+        }
+        .into())
+    }
+}
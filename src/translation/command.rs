@@ -0,0 +1,162 @@
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::process::Stdio;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines};
+use tokio::process::{Child, ChildStdin, ChildStdout, Command};
+use tokio::sync::Mutex;
+
+use super::{Capabilities, Translator};
+use crate::{settings::Settings, translator_object::TranslatorData};
+
+/// Executable configured by the user, kept running between translations.
+///
+/// Each request is written to its stdin as one line of JSON,
+/// `{"text": "...", "source": "ja", "target": "en"}`, and one line is expected
+/// back on stdout, either `{"text": "..."}` or `{"error": "..."}`.
+pub struct ExternalCommand {
+    path: String,
+    /// Split like a shell would, `None` when a quote is left open.
+    args: Option<Vec<String>>,
+    process: Mutex<Option<Process>>,
+}
+
+struct Process {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: Lines<BufReader<ChildStdout>>,
+}
+
+#[derive(Serialize)]
+struct Request<'a> {
+    text: &'a str,
+    source: &'a str,
+    target: &'a str,
+}
+
+#[derive(Deserialize)]
+struct Response {
+    text: Option<String>,
+    error: Option<String>,
+}
+
+impl ExternalCommand {
+    pub fn new(settings: &Settings) -> Self {
+        ExternalCommand {
+            path: settings.command_path().to_string(),
+            args: shlex::split(settings.command_args()),
+            process: Mutex::new(None),
+        }
+    }
+
+    fn spawn(&self) -> Result<Process> {
+        if self.path.is_empty() {
+            bail!("The translation command is not configured");
+        }
+        let args = self
+            .args
+            .as_ref()
+            .context("The translation command arguments have an unclosed quote")?;
+        let mut child = Command::new(&self.path)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()
+            .with_context(|| format!("Failed to run {}", self.path))?;
+        let stdin = child
+            .stdin
+            .take()
+            .context("Failed to open the command stdin")?;
+        let stdout = child
+            .stdout
+            .take()
+            .context("Failed to open the command stdout")?;
+
+        Ok(Process {
+            child,
+            stdin,
+            stdout: BufReader::new(stdout).lines(),
+        })
+    }
+}
+
+impl Process {
+    async fn request(&mut self, line: &str) -> Result<Response> {
+        self.stdin.write_all(line.as_bytes()).await?;
+        self.stdin.write_all(b"\n").await?;
+        self.stdin.flush().await?;
+
+        let response = self
+            .stdout
+            .next_line()
+            .await?
+            .context("The translation command exited")?;
+        serde_json::from_str(&response)
+            .with_context(|| format!("Invalid response from the command: {response}"))
+    }
+}
+
+#[async_trait]
+impl Translator for ExternalCommand {
+    fn id(&self) -> &'static str {
+        "command"
+    }
+
+    fn name(&self) -> &'static str {
+        "External Command"
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities::default()
+    }
+
+    fn fingerprint(&self) -> String {
+        format!("{}\n{:?}", self.path, self.args)
+    }
+
+    async fn languages(&self) -> Result<Vec<TranslatorData>> {
+        Ok(super::default_languages())
+    }
+
+    async fn translate(&self, text: &str, source: &str, target: &str) -> Result<String> {
+        let text = text.trim();
+        if text.is_empty() {
+            return Ok(text.to_string());
+        }
+        let line = serde_json::to_string(&Request {
+            text,
+            source,
+            target,
+        })?;
+
+        let mut process = self.process.lock().await;
+        let running = match process.as_mut() {
+            Some(running) => running,
+            None => process.insert(self.spawn()?),
+        };
+        let response = match running.request(&line).await {
+            Ok(response) => response,
+            Err(err) => {
+                // A broken process is started again on the next request.
+                if let Some(mut broken) = process.take() {
+                    let _ = broken.child.start_kill();
+                }
+                return Err(err);
+            }
+        };
+
+        match (response.text, response.error) {
+            (_, Some(error)) => bail!("Translation command: {error}"),
+            (Some(text), None) => Ok(text),
+            (None, None) => bail!("The translation command returned no text"),
+        }
+    }
+
+    async fn close(&self) {
+        if let Some(mut process) = self.process.lock().await.take() {
+            let _ = process.child.kill().await;
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use fantoccini::{error::CmdError, Locator};
+use tokio::time::{sleep, Duration, Instant};
+
+use super::{webdriver, Capabilities, Translator};
+use crate::{settings::Settings, translator_object::TranslatorData};
+
+pub struct DeepL {
+    session: webdriver::Session,
+}
+
+impl DeepL {
+    pub fn new(settings: &Settings) -> Self {
+        Self {
+            session: webdriver::Session::new(settings),
+        }
+    }
+
+    async fn scrape(&self, text: &str, source: &str, target: &str) -> Result<String> {
+        let client = self.session.client().await?;
+        let url = client.current_url().await?;
+
+        if url.domain() != Some("deepl.com") {
+            let _ = client
+                .goto(&format!(
+                    "https://deepl.com/en/translator#{source}/{target}/",
+                ))
+                .await;
+            webdriver::wait_for_full_load(&client).await?;
+        }
+
+        let inputs = client.find_all(Locator::Css("d-textarea")).await?;
+
+        let input = inputs.first().context("Can't find input to write text")?;
+        let translated = inputs.get(1).context("Can't find the translated text")?;
+
+        let start = Instant::now();
+        while !input.is_displayed().await?
+            || !input.is_enabled().await?
+            || !translated.is_displayed().await?
+        {
+            if start.elapsed() > webdriver::WAIT_TIMEOUT {
+                return Err(CmdError::WaitTimeout.into());
+            }
+            sleep(Duration::from_millis(200)).await;
+        }
+
+        input.send_keys(text).await?;
+        let translated_text = webdriver::get_text_and_clear(&client, translated).await?;
+
+        Ok(translated_text)
+    }
+}
+
+#[async_trait]
+impl Translator for DeepL {
+    fn id(&self) -> &'static str {
+        "deepl"
+    }
+
+    fn name(&self) -> &'static str {
+        "DeepL"
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            webdriver: true,
+            ..Default::default()
+        }
+    }
+
+    async fn languages(&self) -> Result<Vec<TranslatorData>> {
+        Ok(super::default_languages())
+    }
+
+    async fn translate(&self, text: &str, source: &str, target: &str) -> Result<String> {
+        let text = text.trim();
+        if text.is_empty() {
+            return Ok(text.to_string());
+        }
+        let result = self.scrape(text, source, target).await;
+        self.session.recover(result).await
+    }
+
+    async fn close(&self) {
+        self.session.close().await;
+    }
+}
@@ -0,0 +1,204 @@
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use super::{Capabilities, Line, Translator, Usage};
+use crate::{settings::Settings, translator_object::TranslatorData};
+
+/// Formality values accepted by the API and their labels.
+pub const FORMALITIES: [(&str, &str); 5] = [
+    ("default", "Default"),
+    ("prefer_more", "Prefer formal"),
+    ("prefer_less", "Prefer informal"),
+    ("more", "Formal"),
+    ("less", "Informal"),
+];
+
+/// Official DeepL REST API, for both free and pro accounts.
+pub struct DeepLApi {
+    client: reqwest::Client,
+    key: String,
+    formality: String,
+}
+
+#[derive(Serialize)]
+struct Request<'a> {
+    text: Vec<&'a str>,
+    target_lang: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    source_lang: Option<String>,
+    formality: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    context: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Response {
+    translations: Vec<Translation>,
+}
+
+#[derive(Deserialize)]
+struct Translation {
+    text: String,
+}
+
+#[derive(Deserialize)]
+struct UsageResponse {
+    character_count: u64,
+    character_limit: u64,
+}
+
+impl DeepLApi {
+    pub fn new(settings: &Settings) -> Self {
+        DeepLApi {
+            client: reqwest::Client::new(),
+            key: settings.deepl_key().to_string(),
+            formality: settings.deepl_formality().to_string(),
+        }
+    }
+
+    /// Free account keys end with `:fx` and must use their own host.
+    fn url(&self, path: &str) -> String {
+        if self.key.ends_with(":fx") {
+            format!("https://api-free.deepl.com/v2/{path}")
+        } else {
+            format!("https://api.deepl.com/v2/{path}")
+        }
+    }
+
+    async fn send<T: DeserializeOwned>(&self, request: reqwest::RequestBuilder) -> Result<T> {
+        if self.key.is_empty() {
+            bail!("The DeepL API authentication key is not configured");
+        }
+        let response = request
+            .header("Authorization", format!("DeepL-Auth-Key {}", self.key))
+            .send()
+            .await?;
+        match response.status().as_u16() {
+            403 => bail!("DeepL API: invalid authentication key"),
+            456 => bail!("DeepL API: character quota exceeded"),
+            _ => Ok(response.error_for_status()?.json().await?),
+        }
+    }
+
+    async fn request(
+        &self,
+        texts: &[String],
+        context: Option<String>,
+        source: &str,
+        target: &str,
+    ) -> Result<Vec<String>> {
+        let mut translated = texts
+            .iter()
+            .map(|text| text.trim().to_string())
+            .collect::<Vec<String>>();
+        let pending = (0..translated.len())
+            .filter(|i| !translated[*i].is_empty())
+            .collect::<Vec<usize>>();
+        if pending.is_empty() {
+            return Ok(translated);
+        }
+
+        let request = Request {
+            text: pending.iter().map(|i| translated[*i].as_str()).collect(),
+            target_lang: target_code(target),
+            source_lang: source_code(source),
+            formality: &self.formality,
+            context,
+        };
+        let response: Response = self
+            .send(self.client.post(self.url("translate")).json(&request))
+            .await?;
+        if response.translations.len() != pending.len() {
+            bail!("DeepL API returned an unexpected number of translations");
+        }
+
+        for (i, translation) in pending.into_iter().zip(response.translations) {
+            translated[i] = translation.text;
+        }
+        Ok(translated)
+    }
+}
+
+fn source_code(code: &str) -> Option<String> {
+    match code {
+        "auto" => None,
+        code => Some(code.to_uppercase()),
+    }
+}
+
+fn target_code(code: &str) -> String {
+    match code {
+        "en" => "EN-US".to_string(),
+        "pt" => "PT-BR".to_string(),
+        code => code.to_uppercase(),
+    }
+}
+
+#[async_trait]
+impl Translator for DeepLApi {
+    fn id(&self) -> &'static str {
+        "deepl-api"
+    }
+
+    fn name(&self) -> &'static str {
+        "DeepL API"
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            batch: true,
+            context: true,
+            ..Default::default()
+        }
+    }
+
+    fn fingerprint(&self) -> String {
+        self.formality.clone()
+    }
+
+    async fn languages(&self) -> Result<Vec<TranslatorData>> {
+        Ok(super::default_languages())
+    }
+
+    async fn translate(&self, text: &str, source: &str, target: &str) -> Result<String> {
+        let mut translated = self
+            .translate_batch(&[text.to_string()], source, target)
+            .await?;
+        Ok(translated.remove(0))
+    }
+
+    async fn translate_batch(
+        &self,
+        texts: &[String],
+        source: &str,
+        target: &str,
+    ) -> Result<Vec<String>> {
+        self.request(texts, None, source, target).await
+    }
+
+    /// The API takes a single context per request, so the previous lines of
+    /// every text are sent together.
+    async fn translate_with_context(
+        &self,
+        texts: &[String],
+        context: &[Vec<Line>],
+        source: &str,
+        target: &str,
+    ) -> Result<Vec<String>> {
+        let lines = super::merge_context(context)
+            .into_iter()
+            .map(|line| line.source)
+            .collect::<Vec<String>>();
+        let context = (!lines.is_empty()).then(|| lines.join("\n"));
+        self.request(texts, context, source, target).await
+    }
+
+    async fn usage(&self) -> Result<Option<Usage>> {
+        let usage: UsageResponse = self.send(self.client.get(self.url("usage"))).await?;
+        Ok(Some(Usage {
+            characters: usage.character_count,
+            limit: usage.character_limit,
+        }))
+    }
+}
@@ -0,0 +1,80 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use fantoccini::Locator;
+use std::borrow::Cow;
+
+use super::{webdriver, Capabilities, Translator};
+use crate::{settings::Settings, translator_object::TranslatorData};
+
+pub struct Google {
+    session: webdriver::Session,
+}
+
+impl Google {
+    pub fn new(settings: &Settings) -> Self {
+        Self {
+            session: webdriver::Session::new(settings),
+        }
+    }
+
+    async fn scrape(&self, text: &str, source: &str, target: &str) -> Result<String> {
+        let client = self.session.client().await?;
+        let url = client.current_url().await?;
+
+        let mut pairs = url.query_pairs();
+        if url.domain() != Some("translate.google.com.br")
+            || pairs.next() != Some((Cow::Borrowed("sl"), Cow::Borrowed(source)))
+            || pairs.next() != Some((Cow::Borrowed("tl"), Cow::Borrowed(target)))
+        {
+            let _ = client
+                .goto(&format!(
+                    "https://translate.google.com.br/?sl={source}&tl={target}&op=translate",
+                ))
+                .await;
+        }
+
+        let input = client.wait().for_element(Locator::Css("textarea")).await?;
+        input.send_keys(text).await?;
+        let translated = client
+            .wait()
+            .for_element(Locator::Css("[jsname='r5xl4']"))
+            .await?;
+        let translated_text = webdriver::get_text_and_clear(&client, &translated).await?;
+        Ok(translated_text)
+    }
+}
+
+#[async_trait]
+impl Translator for Google {
+    fn id(&self) -> &'static str {
+        "google"
+    }
+
+    fn name(&self) -> &'static str {
+        "Google"
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            webdriver: true,
+            ..Default::default()
+        }
+    }
+
+    async fn languages(&self) -> Result<Vec<TranslatorData>> {
+        Ok(super::default_languages())
+    }
+
+    async fn translate(&self, text: &str, source: &str, target: &str) -> Result<String> {
+        let text = text.trim();
+        if text.is_empty() {
+            return Ok(text.to_string());
+        }
+        let result = self.scrape(text, source, target).await;
+        self.session.recover(result).await
+    }
+
+    async fn close(&self) {
+        self.session.close().await;
+    }
+}
@@ -0,0 +1,111 @@
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use super::{Capabilities, Translator};
+use crate::{settings::Settings, translator_object::TranslatorData};
+
+/// Client for a LibreTranslate server, usually a self-hosted one.
+pub struct LibreTranslate {
+    client: reqwest::Client,
+    url: String,
+    key: String,
+}
+
+#[derive(Serialize)]
+struct Request<'a> {
+    q: &'a str,
+    source: &'a str,
+    target: &'a str,
+    format: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    api_key: Option<&'a str>,
+}
+
+#[derive(Deserialize)]
+struct Response {
+    #[serde(rename = "translatedText")]
+    translated_text: Option<String>,
+    error: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Language {
+    code: String,
+    name: String,
+}
+
+impl LibreTranslate {
+    pub fn new(settings: &Settings) -> Self {
+        LibreTranslate {
+            client: reqwest::Client::new(),
+            url: settings.libre_url().trim_end_matches('/').to_string(),
+            key: settings.libre_key().to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl Translator for LibreTranslate {
+    fn id(&self) -> &'static str {
+        "libre"
+    }
+
+    fn name(&self) -> &'static str {
+        "LibreTranslate"
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities::default()
+    }
+
+    fn fingerprint(&self) -> String {
+        self.url.clone()
+    }
+
+    async fn languages(&self) -> Result<Vec<TranslatorData>> {
+        let languages: Vec<Language> = self
+            .client
+            .get(format!("{}/languages", self.url))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(languages
+            .into_iter()
+            .map(|language| TranslatorData {
+                code: language.code,
+                language: language.name,
+            })
+            .collect())
+    }
+
+    async fn translate(&self, text: &str, source: &str, target: &str) -> Result<String> {
+        let text = text.trim();
+        if text.is_empty() {
+            return Ok(text.to_string());
+        }
+        let request = Request {
+            q: text,
+            source,
+            target,
+            format: "text",
+            api_key: (!self.key.is_empty()).then_some(self.key.as_str()),
+        };
+        let response: Response = self
+            .client
+            .post(format!("{}/translate", self.url))
+            .json(&request)
+            .send()
+            .await?
+            .json()
+            .await?;
+
+        match (response.translated_text, response.error) {
+            (Some(text), _) => Ok(text),
+            (None, Some(error)) => bail!("LibreTranslate: {error}"),
+            (None, None) => bail!("LibreTranslate returned an empty response"),
+        }
+    }
+}
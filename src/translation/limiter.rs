@@ -0,0 +1,285 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use gtk::glib;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::slice;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use tokio::time::{sleep, Duration, Instant};
+
+use super::{Capabilities, Line, Translator, Usage};
+use crate::{profile_object::ProfileData, translator_object::TranslatorData, utils};
+
+/// Share of the monthly quota after which the user is warned.
+const WARNING_RATIO: f64 = 0.9;
+
+static COUNTERS: Lazy<Mutex<Counters>> = Lazy::new(|| Mutex::new(Counters::load()));
+
+/// Limits configured for a provider, zero meaning unlimited.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Limits {
+    pub requests_per_second: f64,
+    pub monthly_characters: u64,
+}
+
+/// Returned instead of sending a request that would go over the monthly quota.
+#[derive(Debug)]
+pub struct QuotaExceeded {
+    pub provider: String,
+    pub limit: u64,
+}
+
+impl fmt::Display for QuotaExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "The monthly quota of {} characters for {} was reached",
+            self.limit, self.provider
+        )
+    }
+}
+
+impl std::error::Error for QuotaExceeded {}
+
+#[derive(Default, Serialize, Deserialize)]
+struct Counters {
+    month: String,
+    characters: HashMap<String, u64>,
+    #[serde(skip)]
+    next_request: HashMap<String, Instant>,
+    #[serde(skip)]
+    warned: HashSet<String>,
+    #[serde(skip)]
+    dirty: bool,
+}
+
+impl Counters {
+    fn load() -> Self {
+        utils::usage_path()
+            .and_then(utils::open_file)
+            .and_then(|file| Ok(serde_json::from_reader::<_, Counters>(file)?))
+            .unwrap_or_default()
+    }
+
+    /// Starts counting from zero when a new month begins.
+    fn roll_over(&mut self) {
+        let month = current_month();
+        if self.month != month {
+            self.month = month;
+            self.characters.clear();
+            self.warned.clear();
+            self.dirty = true;
+        }
+    }
+}
+
+fn counters() -> MutexGuard<'static, Counters> {
+    let mut counters = COUNTERS.lock().unwrap_or_else(PoisonError::into_inner);
+    counters.roll_over();
+    counters
+}
+
+fn current_month() -> String {
+    glib::DateTime::now_local()
+        .and_then(|now| now.format("%Y-%m"))
+        .map(|month| month.to_string())
+        .unwrap_or_default()
+}
+
+/// Characters sent to `provider` this month.
+pub fn used(provider: &str) -> u64 {
+    counters()
+        .characters
+        .get(provider)
+        .copied()
+        .unwrap_or_default()
+}
+
+/// True only the first time `provider` gets close to its monthly quota.
+pub fn should_warn(provider: &str, limits: Limits) -> bool {
+    if limits.monthly_characters == 0 {
+        return false;
+    }
+    let mut counters = counters();
+    let used = counters
+        .characters
+        .get(provider)
+        .copied()
+        .unwrap_or_default();
+    if (used as f64) < limits.monthly_characters as f64 * WARNING_RATIO {
+        return false;
+    }
+    counters.warned.insert(provider.to_string())
+}
+
+/// Writes the counters to disk when they changed since the last save.
+pub fn save() -> Result<()> {
+    let mut counters = counters();
+    if !counters.dirty {
+        return Ok(());
+    }
+    let file = fs::File::create(utils::usage_path()?)?;
+    serde_json::to_writer(file, &*counters)?;
+    counters.dirty = false;
+    Ok(())
+}
+
+/// Waits for the next request slot of `provider`, failing when the request
+/// would go over the monthly quota.
+async fn acquire(provider: &str, limits: Limits, characters: u64) -> Result<()> {
+    if limits.monthly_characters > 0 && used(provider) + characters > limits.monthly_characters {
+        return Err(QuotaExceeded {
+            provider: provider.to_string(),
+            limit: limits.monthly_characters,
+        }
+        .into());
+    }
+    if limits.requests_per_second > 0.0 {
+        let interval = Duration::from_secs_f64(1.0 / limits.requests_per_second);
+        let wait = {
+            let mut counters = counters();
+            let now = Instant::now();
+            let slot = counters
+                .next_request
+                .get(provider)
+                .map_or(now, |next| (*next).max(now));
+            counters
+                .next_request
+                .insert(provider.to_string(), slot + interval);
+            slot - now
+        };
+        sleep(wait).await;
+    }
+    Ok(())
+}
+
+fn record(provider: &str, characters: u64) {
+    let mut counters = counters();
+    *counters.characters.entry(provider.to_string()).or_default() += characters;
+    counters.dirty = true;
+}
+
+fn characters(text: &str) -> u64 {
+    text.chars().count() as u64
+}
+
+/// Provider whose requests are paced and counted against its quota.
+pub struct Limited {
+    inner: Box<dyn Translator>,
+    limits: Limits,
+}
+
+impl Limited {
+    pub fn new(inner: Box<dyn Translator>, limits: Limits) -> Self {
+        Self { inner, limits }
+    }
+}
+
+#[async_trait]
+impl Translator for Limited {
+    fn id(&self) -> &'static str {
+        self.inner.id()
+    }
+
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.inner.capabilities()
+    }
+
+    fn configure(&mut self, profile: &ProfileData) {
+        self.inner.configure(profile);
+    }
+
+    fn fingerprint(&self) -> String {
+        self.inner.fingerprint()
+    }
+
+    async fn languages(&self) -> Result<Vec<TranslatorData>> {
+        self.inner.languages().await
+    }
+
+    async fn translate(&self, text: &str, source: &str, target: &str) -> Result<String> {
+        let characters = characters(text);
+        acquire(self.id(), self.limits, characters).await?;
+        let translated = self.inner.translate(text, source, target).await?;
+        record(self.id(), characters);
+        Ok(translated)
+    }
+
+    async fn translate_batch(
+        &self,
+        texts: &[String],
+        source: &str,
+        target: &str,
+    ) -> Result<Vec<String>> {
+        if !self.capabilities().batch {
+            let mut translated = Vec::with_capacity(texts.len());
+            for text in texts {
+                translated.push(self.translate(text, source, target).await?);
+            }
+            return Ok(translated);
+        }
+
+        let characters = texts.iter().map(|text| characters(text)).sum();
+        acquire(self.id(), self.limits, characters).await?;
+        let translated = self.inner.translate_batch(texts, source, target).await?;
+        record(self.id(), characters);
+        Ok(translated)
+    }
+
+    async fn translate_with_context(
+        &self,
+        texts: &[String],
+        context: &[Vec<Line>],
+        source: &str,
+        target: &str,
+    ) -> Result<Vec<String>> {
+        if !self.capabilities().context {
+            return self.translate_batch(texts, source, target).await;
+        }
+        if !self.capabilities().batch {
+            // Each text is its own request, each one waits for its turn.
+            let mut translated = Vec::with_capacity(texts.len());
+            for (i, text) in texts.iter().enumerate() {
+                let lines = context.get(i).cloned().unwrap_or_default();
+                let characters = characters(text);
+                acquire(self.id(), self.limits, characters).await?;
+                let result = self
+                    .inner
+                    .translate_with_context(
+                        slice::from_ref(text),
+                        slice::from_ref(&lines),
+                        source,
+                        target,
+                    )
+                    .await?;
+                record(self.id(), characters);
+                translated.push(result.into_iter().next().unwrap_or_default());
+            }
+            return Ok(translated);
+        }
+
+        let characters = texts.iter().map(|text| characters(text)).sum();
+        acquire(self.id(), self.limits, characters).await?;
+        let translated = self
+            .inner
+            .translate_with_context(texts, context, source, target)
+            .await?;
+        record(self.id(), characters);
+        Ok(translated)
+    }
+
+    async fn usage(&self) -> Result<Option<Usage>> {
+        self.inner.usage().await
+    }
+
+    async fn close(&self) {
+        self.inner.close().await;
+    }
+}
@@ -0,0 +1,255 @@
+pub mod cache;
+mod chain;
+mod command;
+mod deepl;
+pub mod deepl_api;
+mod google;
+mod libre;
+pub mod limiter;
+mod ollama;
+pub mod openai;
+pub mod webdriver;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use once_cell::sync::Lazy;
+use std::fmt;
+use tokio::time::{sleep, Duration};
+
+use crate::{profile_object::ProfileData, settings::Settings, translator_object::TranslatorData};
+
+pub use chain::Chain;
+pub use command::ExternalCommand;
+pub use deepl::DeepL;
+pub use deepl_api::DeepLApi;
+pub use google::Google;
+pub use libre::LibreTranslate;
+pub use ollama::Ollama;
+pub use openai::OpenAi;
+
+/// What a provider needs from, or offers to, the translation loop.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Capabilities {
+    /// The provider drives a browser through the local webdriver.
+    pub webdriver: bool,
+    /// The provider translates several texts in one request on its own.
+    pub batch: bool,
+    /// The provider can use the previous lines to keep its translations consistent.
+    pub context: bool,
+}
+
+/// A line translated before the current text, kept as context.
+#[derive(Clone, Debug, Default)]
+pub struct Line {
+    pub source: String,
+    pub target: String,
+}
+
+/// Returned when a provider takes longer than its configured timeout.
+#[derive(Debug)]
+pub struct TimedOut {
+    pub provider: &'static str,
+    pub seconds: u64,
+}
+
+impl fmt::Display for TimedOut {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} didn't answer within {} seconds",
+            self.provider, self.seconds
+        )
+    }
+}
+
+impl std::error::Error for TimedOut {}
+
+/// Characters consumed in the current billing period.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Usage {
+    pub characters: u64,
+    pub limit: u64,
+}
+
+#[async_trait]
+pub trait Translator: Send + Sync {
+    /// Stable identifier stored in the settings.
+    fn id(&self) -> &'static str;
+
+    /// Name shown to the user.
+    fn name(&self) -> &'static str;
+
+    fn capabilities(&self) -> Capabilities;
+
+    /// Applies the provider options stored in the profile.
+    fn configure(&mut self, _profile: &ProfileData) {}
+
+    /// Options that change the translations, like the model or the prompt, so
+    /// the cache doesn't serve what other options produced.
+    fn fingerprint(&self) -> String {
+        String::new()
+    }
+
+    /// Target languages accepted by the provider.
+    async fn languages(&self) -> Result<Vec<TranslatorData>>;
+
+    async fn translate(&self, text: &str, source: &str, target: &str) -> Result<String>;
+
+    /// Translates every text, one request at a time unless the provider can do better.
+    async fn translate_batch(
+        &self,
+        texts: &[String],
+        source: &str,
+        target: &str,
+    ) -> Result<Vec<String>> {
+        let mut translated = Vec::with_capacity(texts.len());
+        for text in texts {
+            sleep(Duration::from_millis(200)).await;
+            translated.push(self.translate(text, source, target).await?);
+        }
+        Ok(translated)
+    }
+
+    /// Translates every text knowing the lines that came before it, where
+    /// `context[i]` holds the lines preceding `texts[i]`, oldest first.
+    async fn translate_with_context(
+        &self,
+        texts: &[String],
+        _context: &[Vec<Line>],
+        source: &str,
+        target: &str,
+    ) -> Result<Vec<String>> {
+        self.translate_batch(texts, source, target).await
+    }
+
+    /// Quota consumption reported by the provider, if it has one.
+    async fn usage(&self) -> Result<Option<Usage>> {
+        Ok(None)
+    }
+
+    /// Releases any resource held by the provider.
+    async fn close(&self) {}
+}
+
+/// Line placed between the texts joined in a single request.
+const DELIMITER: &str = "###";
+
+/// Translates every text with as few requests as possible.
+///
+/// Providers without native batching get all the texts joined by [`DELIMITER`]
+/// lines in one request. When the delimiters don't survive the translation the
+/// texts are sent one by one. Providers with the context capability also get
+/// the previous lines, when there are any, merged for a joined request.
+pub async fn translate_all(
+    translator: &dyn Translator,
+    texts: &[String],
+    context: &[Vec<Line>],
+    source: &str,
+    target: &str,
+) -> Result<Vec<String>> {
+    let with_context =
+        translator.capabilities().context && context.iter().any(|lines| !lines.is_empty());
+    let one_by_one = async {
+        if with_context {
+            translator
+                .translate_with_context(texts, context, source, target)
+                .await
+        } else {
+            translator.translate_batch(texts, source, target).await
+        }
+    };
+    if translator.capabilities().batch
+        || texts.len() < 2
+        || texts.iter().any(|text| text.contains(DELIMITER))
+    {
+        return one_by_one.await;
+    }
+
+    let joined = texts
+        .iter()
+        .map(|text| text.trim())
+        .collect::<Vec<&str>>()
+        .join(&format!("\n{DELIMITER}\n"));
+    let translated = if with_context {
+        translator
+            .translate_with_context(&[joined], &[merge_context(context)], source, target)
+            .await?
+            .pop()
+            .unwrap_or_default()
+    } else {
+        translator.translate(&joined, source, target).await?
+    };
+    let parts = split_joined(&translated);
+    if parts.len() == texts.len() {
+        return Ok(parts);
+    }
+    one_by_one.await
+}
+
+/// Lines of every text's context, without repeating the ones they share.
+pub fn merge_context(context: &[Vec<Line>]) -> Vec<Line> {
+    let mut merged: Vec<Line> = vec![];
+    for line in context.iter().flatten() {
+        if !merged.iter().any(|known| known.source == line.source) {
+            merged.push(line.clone());
+        }
+    }
+    merged
+}
+
+fn split_joined(text: &str) -> Vec<String> {
+    let mut parts = vec![];
+    let mut current = vec![];
+    for line in text.lines() {
+        if line.trim() == DELIMITER {
+            parts.push(current.join("\n").trim().to_string());
+            current.clear();
+        } else {
+            current.push(line);
+        }
+    }
+    parts.push(current.join("\n").trim().to_string());
+    parts
+}
+
+/// Every available provider, in the order they are shown in the menu.
+pub fn registry(settings: &Settings) -> Vec<Box<dyn Translator>> {
+    vec![
+        Box::new(Google::new(settings)),
+        Box::new(DeepL::new(settings)),
+        Box::new(LibreTranslate::new(settings)),
+        Box::new(DeepLApi::new(settings)),
+        Box::new(OpenAi::new(settings)),
+        Box::new(Ollama::new(settings)),
+        Box::new(ExternalCommand::new(settings)),
+    ]
+}
+
+/// Name of the provider registered as `id`, without building the provider.
+pub fn provider_name(id: &str) -> &'static str {
+    static NAMES: Lazy<Vec<(&'static str, &'static str)>> = Lazy::new(|| {
+        registry(&Settings::default())
+            .iter()
+            .map(|translator| (translator.id(), translator.name()))
+            .collect()
+    });
+    NAMES
+        .iter()
+        .find(|(provider, _)| *provider == id)
+        .map_or("Google", |(_, name)| name)
+}
+
+/// Provider registered as `id`, falling back to Google for unknown ids.
+pub fn provider(id: &str, settings: &Settings) -> Box<dyn Translator> {
+    registry(settings)
+        .into_iter()
+        .find(|translator| translator.id() == id)
+        .unwrap_or_else(|| Box::new(Google::new(settings)))
+}
+
+fn default_languages() -> Vec<TranslatorData> {
+    TranslatorData::all_languages()
+        .into_iter()
+        .filter(|language| language.code != "nt")
+        .collect()
+}
@@ -0,0 +1,166 @@
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use super::{openai, Capabilities, Line, Translator};
+use crate::{profile_object::ProfileData, settings::Settings, translator_object::TranslatorData};
+
+/// Ollama server using its native API.
+pub struct Ollama {
+    client: reqwest::Client,
+    url: String,
+    prompt: String,
+    model: String,
+    temperature: f64,
+    keep_alive: String,
+}
+
+#[derive(Serialize)]
+struct Request<'a> {
+    model: &'a str,
+    prompt: String,
+    stream: bool,
+    options: Options,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    keep_alive: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+struct Options {
+    temperature: f64,
+}
+
+#[derive(Deserialize)]
+struct Response {
+    response: Option<String>,
+    error: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Tags {
+    models: Vec<Model>,
+}
+
+#[derive(Deserialize)]
+struct Model {
+    name: String,
+}
+
+impl Ollama {
+    pub fn new(settings: &Settings) -> Self {
+        Ollama {
+            client: reqwest::Client::new(),
+            url: settings.ollama_url().trim_end_matches('/').to_string(),
+            prompt: settings.llm_prompt().to_string(),
+            model: String::new(),
+            temperature: 0.0,
+            keep_alive: String::new(),
+        }
+    }
+
+    /// Names of the models installed on the server.
+    pub async fn models(&self) -> Result<Vec<String>> {
+        let tags: Tags = self
+            .client
+            .get(format!("{}/api/tags", self.url))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(tags.models.into_iter().map(|model| model.name).collect())
+    }
+
+    async fn generate(
+        &self,
+        text: &str,
+        context: &[Line],
+        source: &str,
+        target: &str,
+    ) -> Result<String> {
+        let text = text.trim();
+        if text.is_empty() {
+            return Ok(text.to_string());
+        }
+        if self.model.is_empty() {
+            bail!("Select an Ollama model for this profile");
+        }
+        let request = Request {
+            model: &self.model,
+            prompt: openai::prompt(&self.prompt, text, context, source, target),
+            stream: false,
+            options: Options {
+                temperature: self.temperature,
+            },
+            keep_alive: (!self.keep_alive.is_empty()).then_some(self.keep_alive.as_str()),
+        };
+        let response: Response = self
+            .client
+            .post(format!("{}/api/generate", self.url))
+            .json(&request)
+            .send()
+            .await?
+            .json()
+            .await?;
+
+        match (response.response, response.error) {
+            (_, Some(error)) => bail!("Ollama: {error}"),
+            (Some(text), None) => Ok(text.trim().to_string()),
+            (None, None) => bail!("Ollama returned an empty response"),
+        }
+    }
+}
+
+#[async_trait]
+impl Translator for Ollama {
+    fn id(&self) -> &'static str {
+        "ollama"
+    }
+
+    fn name(&self) -> &'static str {
+        "Ollama"
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            context: true,
+            ..Default::default()
+        }
+    }
+
+    fn configure(&mut self, profile: &ProfileData) {
+        self.model = profile.ollama_model.clone();
+        self.temperature = profile.ollama_temperature;
+        self.keep_alive = profile.ollama_keep_alive.trim().to_string();
+    }
+
+    fn fingerprint(&self) -> String {
+        format!(
+            "{}\n{}\n{}\n{}",
+            self.url, self.model, self.temperature, self.prompt
+        )
+    }
+
+    async fn languages(&self) -> Result<Vec<TranslatorData>> {
+        Ok(super::default_languages())
+    }
+
+    async fn translate(&self, text: &str, source: &str, target: &str) -> Result<String> {
+        self.generate(text, &[], source, target).await
+    }
+
+    async fn translate_with_context(
+        &self,
+        texts: &[String],
+        context: &[Vec<Line>],
+        source: &str,
+        target: &str,
+    ) -> Result<Vec<String>> {
+        let mut translated = Vec::with_capacity(texts.len());
+        for (i, text) in texts.iter().enumerate() {
+            let lines = context.get(i).map_or(&[][..], Vec::as_slice);
+            translated.push(self.generate(text, lines, source, target).await?);
+        }
+        Ok(translated)
+    }
+}
@@ -0,0 +1,183 @@
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use super::{Capabilities, Line, Translator};
+use crate::{settings::Settings, translator_object::TranslatorData};
+
+pub const DEFAULT_PROMPT: &str =
+    "Translate the following video game text from {source} to {target}. \
+Keep the tone and the voice of the character. Reply with the translation only.\n\n{text}";
+
+/// Any server exposing an OpenAI style `/chat/completions` endpoint,
+/// like llama.cpp, vLLM or the OpenAI API itself.
+pub struct OpenAi {
+    client: reqwest::Client,
+    url: String,
+    key: String,
+    model: String,
+    prompt: String,
+}
+
+#[derive(Serialize)]
+struct Request<'a> {
+    model: &'a str,
+    messages: Vec<Message>,
+    stream: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Message {
+    role: String,
+    content: String,
+}
+
+#[derive(Deserialize)]
+struct Response {
+    choices: Vec<Choice>,
+}
+
+#[derive(Deserialize)]
+struct Choice {
+    message: Message,
+}
+
+impl OpenAi {
+    pub fn new(settings: &Settings) -> Self {
+        OpenAi {
+            client: reqwest::Client::new(),
+            url: settings.openai_url().trim_end_matches('/').to_string(),
+            key: settings.openai_key().to_string(),
+            model: settings.openai_model().to_string(),
+            prompt: settings.llm_prompt().to_string(),
+        }
+    }
+
+    async fn complete(
+        &self,
+        text: &str,
+        context: &[Line],
+        source: &str,
+        target: &str,
+    ) -> Result<String> {
+        let text = text.trim();
+        if text.is_empty() {
+            return Ok(text.to_string());
+        }
+        let request = Request {
+            model: &self.model,
+            messages: vec![Message {
+                role: "user".to_string(),
+                content: prompt(&self.prompt, text, context, source, target),
+            }],
+            stream: false,
+        };
+        let mut builder = self
+            .client
+            .post(format!("{}/chat/completions", self.url))
+            .json(&request);
+        if !self.key.is_empty() {
+            builder = builder.bearer_auth(&self.key);
+        }
+
+        let response = builder.send().await?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            bail!("Chat completion failed with {status}: {body}");
+        }
+        let response: Response = response.json().await?;
+        let choice = response
+            .choices
+            .into_iter()
+            .next()
+            .context("The chat completion returned no choices")?;
+        Ok(choice.message.content.trim().to_string())
+    }
+}
+
+/// Fills the `{source}`, `{target}`, `{context}` and `{text}` placeholders of
+/// a prompt template. Templates without `{context}` get the previous lines
+/// before them.
+pub fn prompt(template: &str, text: &str, context: &[Line], source: &str, target: &str) -> String {
+    let context = context_section(context);
+    let template = if context.is_empty() || template.contains("{context}") {
+        template.to_string()
+    } else {
+        format!("{{context}}\n\n{template}")
+    };
+    template
+        .replace("{source}", &language_name(source))
+        .replace("{target}", &language_name(target))
+        .replace("{context}", &context)
+        .replace("{text}", text)
+}
+
+fn context_section(context: &[Line]) -> String {
+    if context.is_empty() {
+        return String::new();
+    }
+    let lines = context
+        .iter()
+        .map(|line| format!("{} => {}", line.source, line.target))
+        .collect::<Vec<String>>();
+    format!(
+        "Previous lines and their translations, for context only:\n{}",
+        lines.join("\n")
+    )
+}
+
+fn language_name(code: &str) -> String {
+    if code == "auto" {
+        return "the original language".to_string();
+    }
+    TranslatorData::all_languages()
+        .into_iter()
+        .find(|language| language.code == code)
+        .map_or_else(|| code.to_string(), |language| language.language)
+}
+
+#[async_trait]
+impl Translator for OpenAi {
+    fn id(&self) -> &'static str {
+        "openai"
+    }
+
+    fn name(&self) -> &'static str {
+        "OpenAI Compatible"
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            context: true,
+            ..Default::default()
+        }
+    }
+
+    fn fingerprint(&self) -> String {
+        format!("{}\n{}\n{}", self.url, self.model, self.prompt)
+    }
+
+    async fn languages(&self) -> Result<Vec<TranslatorData>> {
+        Ok(super::default_languages())
+    }
+
+    async fn translate(&self, text: &str, source: &str, target: &str) -> Result<String> {
+        self.complete(text, &[], source, target).await
+    }
+
+    async fn translate_with_context(
+        &self,
+        texts: &[String],
+        context: &[Vec<Line>],
+        source: &str,
+        target: &str,
+    ) -> Result<Vec<String>> {
+        let mut translated = Vec::with_capacity(texts.len());
+        for (i, text) in texts.iter().enumerate() {
+            let lines = context.get(i).map_or(&[][..], Vec::as_slice);
+            translated.push(self.complete(text, lines, source, target).await?);
+        }
+        Ok(translated)
+    }
+}
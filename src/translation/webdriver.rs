@@ -0,0 +1,653 @@
+use fantoccini::elements::Element;
+use fantoccini::error::NewSessionError;
+use flate2::read::GzDecoder;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use tokio::process::Command;
+use tokio::sync::Mutex;
+use tokio::time::{sleep, Duration, Instant};
+
+use anyhow::{bail, Context, Result};
+use fantoccini::actions::{InputSource, KeyAction, KeyActions};
+use fantoccini::key::Key;
+use fantoccini::Client;
+use regex::Regex;
+
+use crate::{settings::Settings, utils};
+/// Browser driven through the webdriver.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Backend {
+    #[default]
+    Chrome,
+    Firefox,
+}
+
+impl Backend {
+    pub const ALL: [Backend; 2] = [Backend::Chrome, Backend::Firefox];
+
+    pub fn from_id(id: &str) -> Self {
+        match id {
+            "firefox" => Backend::Firefox,
+            _ => Backend::Chrome,
+        }
+    }
+
+    /// Stable identifier stored in the settings.
+    pub fn id(self) -> &'static str {
+        match self {
+            Backend::Chrome => "chrome",
+            Backend::Firefox => "firefox",
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Backend::Chrome => "Chrome (chromedriver)",
+            Backend::Firefox => "Firefox (geckodriver)",
+        }
+    }
+
+    fn driver(self) -> &'static str {
+        match self {
+            Backend::Chrome => "chromedriver",
+            Backend::Firefox => "geckodriver",
+        }
+    }
+}
+
+/// Webdriver backend and executables chosen in the settings.
+#[derive(Clone, Default, Debug, PartialEq)]
+pub struct Browser {
+    pub backend: Backend,
+    /// Driver executable used instead of the downloaded one.
+    pub driver_path: String,
+    /// Browser executable used instead of the one the driver finds.
+    pub binary_path: String,
+}
+
+impl Browser {
+    pub fn new(settings: &Settings) -> Self {
+        Self {
+            backend: settings.webdriver_backend(),
+            driver_path: settings.webdriver_path().to_string(),
+            binary_path: settings.browser_path().to_string(),
+        }
+    }
+
+    fn capabilities(&self) -> serde_json::map::Map<String, serde_json::Value> {
+        let mut caps = serde_json::map::Map::new();
+        match self.backend {
+            // --disable-gcm --disable-sync --disable-cloud-import
+            Backend::Chrome => {
+                let mut opts = serde_json::json!({
+                    "args": [
+                        "--headless=new",
+                        "--disable-software-rasterizer",
+                        "--disable-gpu",
+                        "--no-sandbox",
+                        "--disable-extensions",
+                        "--disable-default-apps",
+                        "--disable-background-timer-throttling",
+                        "--disable-backgrounding-occluded-windows",
+                        "--disable-breakpad",
+                        "--no-first-run",
+                        "--no-zygote",
+                        "--disable-renderer-backgrounding",
+                        "--disable-notifications",
+                        "--disable-background-networking",
+                        "--disable-component-update",
+                        "--log-level=3"
+                    ],
+                });
+                if !self.binary_path.is_empty() {
+                    opts["binary"] = self.binary_path.clone().into();
+                }
+                caps.insert("goog:chromeOptions".to_string(), opts);
+            }
+            Backend::Firefox => {
+                let mut opts = serde_json::json!({
+                    "args": ["-headless"],
+                    "prefs": {
+                        "app.update.auto": false,
+                        "browser.shell.checkDefaultBrowser": false,
+                        "datareporting.policy.dataSubmissionEnabled": false,
+                        "toolkit.telemetry.enabled": false
+                    },
+                    "log": { "level": "fatal" }
+                });
+                if !self.binary_path.is_empty() {
+                    opts["binary"] = self.binary_path.clone().into();
+                }
+                caps.insert("moz:firefoxOptions".to_string(), opts);
+            }
+        }
+        caps
+    }
+}
+
+pub async fn client(browser: &Browser) -> Result<Client, NewSessionError> {
+    fantoccini::ClientBuilder::native()
+        .capabilities(browser.capabilities())
+        .connect("http://localhost:50682")
+        .await
+}
+
+/// Lazily connected webdriver client shared by the scraping providers.
+#[derive(Default)]
+pub struct Session {
+    browser: Browser,
+    client: Mutex<Option<Client>>,
+}
+
+impl Session {
+    pub fn new(settings: &Settings) -> Self {
+        Self {
+            browser: Browser::new(settings),
+            ..Default::default()
+        }
+    }
+
+    /// Current client, connecting a new session when there is none.
+    pub async fn client(&self) -> Result<Client> {
+        let mut current = self.client.lock().await;
+        if let Some(client) = current.as_ref() {
+            return Ok(client.clone());
+        }
+        let client =
+            client(&self.browser)
+                .await
+                .map_err(|err| match version_mismatch(&self.browser) {
+                    Some(mismatch) => anyhow::anyhow!("{err}\n\n{mismatch}"),
+                    None => err.into(),
+                })?;
+        *current = Some(client.clone());
+        Ok(client)
+    }
+
+    /// Drops the session after a failed request, so the next one reconnects
+    /// instead of reusing a session the driver may have lost.
+    pub async fn recover<T>(&self, result: Result<T>) -> Result<T> {
+        if result.is_err() {
+            self.close().await;
+        }
+        result
+    }
+
+    pub async fn close(&self) {
+        if let Some(client) = self.client.lock().await.take() {
+            let _ = client.close().await;
+        }
+    }
+}
+
+/// Checks that the driver listening on `port` answers its status endpoint.
+pub async fn webdriver_status(port: u32) -> Result<()> {
+    reqwest::Client::builder()
+        .timeout(Duration::from_secs(3))
+        .build()?
+        .get(format!("http://localhost:{port}/status"))
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<serde_json::Value>()
+        .await?;
+    Ok(())
+}
+
+/// Longest a page may take to show what a provider waits for.
+pub const WAIT_TIMEOUT: Duration = Duration::from_secs(30);
+
+async fn clean_field(client: &Client) -> Result<(), fantoccini::error::CmdError> {
+    let keys = KeyActions::new("keyboard".to_string())
+        .then(KeyAction::Down {
+            value: Key::Control.into(),
+        })
+        .then(KeyAction::Down { value: 'a' })
+        .then(KeyAction::Up { value: 'a' })
+        .then(KeyAction::Up {
+            value: Key::Control.into(),
+        })
+        .then(KeyAction::Down {
+            value: Key::Backspace.into(),
+        })
+        .then(KeyAction::Up {
+            value: Key::Backspace.into(),
+        });
+
+    client.perform_actions(keys).await
+}
+
+pub async fn get_text_and_clear(
+    client: &Client,
+    element: &Element,
+) -> Result<String, fantoccini::error::CmdError> {
+    let start = Instant::now();
+    let mut current_value: String;
+    loop {
+        current_value = element.text().await?;
+        if !current_value.trim().is_empty() {
+            break;
+        }
+        if start.elapsed() > WAIT_TIMEOUT {
+            return Err(fantoccini::error::CmdError::WaitTimeout);
+        }
+        sleep(Duration::from_millis(200)).await;
+    }
+    clean_field(client).await?;
+    while !element.text().await?.trim().is_empty() {
+        if start.elapsed() > WAIT_TIMEOUT {
+            return Err(fantoccini::error::CmdError::WaitTimeout);
+        }
+        sleep(Duration::from_millis(200)).await;
+    }
+
+    Ok(current_value)
+}
+
+pub async fn wait_for_full_load(
+    client: &fantoccini::Client,
+) -> Result<(), fantoccini::error::CmdError> {
+    wait_for_page_load(client).await?;
+    wait_for_network_idle(client).await?;
+
+    Ok(())
+}
+
+async fn wait_for_page_load(
+    client: &fantoccini::Client,
+) -> Result<(), fantoccini::error::CmdError> {
+    let start = Instant::now();
+
+    loop {
+        let ready_state: String = client
+            .execute("return document.readyState", vec![])
+            .await?
+            .as_str()
+            .unwrap_or("")
+            .to_string();
+
+        if ready_state == "complete" {
+            break;
+        }
+
+        if start.elapsed() > WAIT_TIMEOUT {
+            return Err(fantoccini::error::CmdError::WaitTimeout);
+        }
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    Ok(())
+}
+
+async fn wait_for_network_idle(
+    client: &fantoccini::Client,
+) -> Result<(), fantoccini::error::CmdError> {
+    let start = Instant::now();
+
+    loop {
+        let active_requests: i64 = client
+            .execute(
+                "return window.performance.getEntriesByType('resource').filter(
+                r => r.responseEnd > (performance.now() - 200)
+            ).length",
+                vec![],
+            )
+            .await?
+            .as_i64()
+            .unwrap_or(0);
+
+        if active_requests == 0 {
+            break;
+        }
+
+        if start.elapsed() > WAIT_TIMEOUT {
+            return Err(fantoccini::error::CmdError::WaitTimeout);
+        }
+
+        tokio::time::sleep(Duration::from_millis(200)).await;
+    }
+    Ok(())
+}
+
+/// Platform name used in the driver downloads and the driver executable name.
+fn platform_and_name(backend: Backend) -> Result<(String, String)> {
+    let platform = match (backend, std::env::consts::OS, std::env::consts::ARCH) {
+        (Backend::Chrome, "windows", "x86") => "win32",
+        (Backend::Chrome, "windows", "x86_64") => "win64",
+        (Backend::Chrome, "linux", "x86_64") => "linux64",
+        (Backend::Chrome, "macos", "x86_64") => "mac-x64",
+        (Backend::Chrome, "macos", "aarch64") => "mac-arm64",
+        (Backend::Firefox, "windows", "x86") => "win32",
+        (Backend::Firefox, "windows", "x86_64") => "win64",
+        (Backend::Firefox, "windows", "aarch64") => "win-aarch64",
+        (Backend::Firefox, "linux", "x86") => "linux32",
+        (Backend::Firefox, "linux", "x86_64") => "linux64",
+        (Backend::Firefox, "linux", "aarch64") => "linux-aarch64",
+        (Backend::Firefox, "macos", "x86_64") => "macos",
+        (Backend::Firefox, "macos", "aarch64") => "macos-aarch64",
+        _ => {
+            return Err(anyhow::Error::msg(format!(
+                "Unsupported OS for {}: {}. with the arch: {}",
+                backend.driver(),
+                std::env::consts::OS,
+                std::env::consts::ARCH
+            )))
+        }
+    };
+    let bin_name = if cfg!(windows) {
+        format!("{}.exe", backend.driver())
+    } else {
+        backend.driver().to_string()
+    };
+    Ok((platform.to_string(), bin_name))
+}
+
+pub fn webdriver_path(browser: &Browser) -> Result<PathBuf> {
+    if !browser.driver_path.is_empty() {
+        return Ok(PathBuf::from(&browser.driver_path));
+    }
+    let (_, bin_name) = platform_and_name(browser.backend)?;
+    let temp_dir = utils::system_path()?.join(browser.backend.driver());
+    Ok(temp_dir.join(bin_name))
+}
+
+pub fn run_webdriver(browser: &Browser, port: u32) -> Result<tokio::process::Child> {
+    let bin_path = webdriver_path(browser)?;
+
+    let mut command = Command::new(&bin_path);
+    match browser.backend {
+        Backend::Chrome => command.arg(format!("--port={port}")),
+        Backend::Firefox => command.arg("--port").arg(port.to_string()),
+    };
+    let child = command
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+
+    Ok(child)
+}
+
+/// Downloads the driver matching the installed browser and returns its version.
+pub async fn download_webdriver(browser: &Browser) -> Result<String> {
+    let bin_path = managed_driver_path(browser)?;
+    let (platform, bin_name) = platform_and_name(browser.backend)?;
+
+    let expected = match browser.backend {
+        Backend::Chrome => {
+            let chrome = chrome_version(browser);
+            Some(download_chromedriver(&bin_path, &platform, &bin_name, chrome.as_deref()).await?)
+        }
+        Backend::Firefox => {
+            download_geckodriver(&bin_path, &platform, &bin_name).await?;
+            None
+        }
+    };
+    finish_install(browser, &bin_path, expected.as_deref())
+}
+
+/// Installs the driver from a local zip, tar.gz or executable, for machines
+/// without network access. Returns the installed version.
+pub fn install_webdriver_from(browser: &Browser, source: &Path) -> Result<String> {
+    let bin_path = managed_driver_path(browser)?;
+    let (_, bin_name) = platform_and_name(browser.backend)?;
+    let file_name = source
+        .file_name()
+        .and_then(OsStr::to_str)
+        .unwrap_or_default()
+        .to_lowercase();
+
+    if file_name.ends_with(".zip") {
+        extract_zip(&std::fs::read(source)?, &bin_name, &bin_path)?;
+    } else if file_name.ends_with(".tar.gz") || file_name.ends_with(".tgz") {
+        extract_tar(&std::fs::read(source)?, &bin_name, &bin_path)?;
+    } else {
+        std::fs::copy(source, &bin_path)?;
+    }
+    finish_install(browser, &bin_path, None)
+}
+
+/// Path of the driver managed by the app, refusing to touch a user provided one.
+fn managed_driver_path(browser: &Browser) -> Result<PathBuf> {
+    if !browser.driver_path.is_empty() {
+        bail!(
+            "The {} set in the preferences was not found at {}",
+            browser.backend.driver(),
+            browser.driver_path
+        );
+    }
+    let path = webdriver_path(browser)?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    Ok(path)
+}
+
+/// Makes the driver executable, checks that it runs and is the expected
+/// version, and records that version.
+fn finish_install(browser: &Browser, bin_path: &Path, expected: Option<&str>) -> Result<String> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(bin_path, std::fs::Permissions::from_mode(0o755))?;
+    }
+
+    let version = driver_version(bin_path);
+    let verified = match (&version, expected) {
+        (Some(version), Some(expected)) => version == expected,
+        (Some(_), None) => true,
+        (None, _) => false,
+    };
+    if !verified {
+        let _ = std::fs::remove_file(bin_path);
+        bail!(
+            "The installed {} failed verification, expected version {} but found {}",
+            browser.backend.driver(),
+            expected.unwrap_or("any"),
+            version.as_deref().unwrap_or("none")
+        );
+    }
+    let version = version.unwrap_or_default();
+    std::fs::write(version_path(browser)?, &version)?;
+    Ok(version)
+}
+
+fn version_path(browser: &Browser) -> Result<PathBuf> {
+    Ok(utils::system_path()?
+        .join(browser.backend.driver())
+        .join("version"))
+}
+
+/// Version of the driver in use, as recorded when it was installed.
+pub fn installed_version(browser: &Browser) -> Option<String> {
+    if browser.driver_path.is_empty() {
+        if let Ok(version) =
+            version_path(browser).and_then(|path| Ok(std::fs::read_to_string(path)?))
+        {
+            return Some(version.trim().to_string());
+        }
+    }
+    driver_version(&webdriver_path(browser).ok()?)
+}
+
+/// Explains why the installed chromedriver can't drive the installed Chrome.
+pub fn version_mismatch(browser: &Browser) -> Option<String> {
+    if browser.backend != Backend::Chrome {
+        return None;
+    }
+    let chrome = chrome_version(browser)?;
+    let driver = installed_version(browser)?;
+    if major(&chrome) == major(&driver) {
+        return None;
+    }
+    Some(format!(
+        "Chromedriver {driver} does not support the installed Chrome {chrome}. \
+         Reinstall the webdriver to download the matching version."
+    ))
+}
+
+/// Executables tried when no browser path is set.
+const CHROME_BINARIES: [&str; 5] = [
+    "google-chrome",
+    "google-chrome-stable",
+    "chromium",
+    "chromium-browser",
+    "/Applications/Google Chrome.app/Contents/MacOS/Google Chrome",
+];
+
+/// Version of the Chrome installed on this machine.
+pub fn chrome_version(browser: &Browser) -> Option<String> {
+    let output = if cfg!(windows) {
+        if browser.binary_path.is_empty() {
+            ["HKCU", "HKLM"].iter().find_map(|hive| {
+                let key = format!("{hive}\\Software\\Google\\Chrome\\BLBeacon");
+                command_output("reg", &["query", &key, "/v", "version"])
+            })
+        } else {
+            let script = format!(
+                "(Get-Item '{}').VersionInfo.ProductVersion",
+                browser.binary_path
+            );
+            command_output("powershell", &["-NoProfile", "-Command", &script])
+        }
+    } else if browser.binary_path.is_empty() {
+        CHROME_BINARIES
+            .iter()
+            .find_map(|binary| command_output(binary, &["--version"]))
+    } else {
+        command_output(&browser.binary_path, &["--version"])
+    };
+    parse_version(&output?)
+}
+
+fn driver_version(bin_path: &Path) -> Option<String> {
+    parse_version(&command_output(bin_path.to_str()?, &["--version"])?)
+}
+
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let mut command = std::process::Command::new(program);
+    command
+        .args(args)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null());
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        // CREATE_NO_WINDOW, the app has no console to reuse.
+        command.creation_flags(0x0800_0000);
+    }
+    let output = command.output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+fn parse_version(text: &str) -> Option<String> {
+    let version = Regex::new(r"\d+(\.\d+){2,3}").ok()?;
+    version.find(text).map(|found| found.as_str().to_string())
+}
+
+fn major(version: &str) -> &str {
+    version.split('.').next().unwrap_or_default()
+}
+
+/// Downloads the chromedriver of the same milestone as `chrome`, or the latest
+/// stable one when Chrome wasn't found. Returns the downloaded version.
+async fn download_chromedriver(
+    bin_path: &Path,
+    arch: &str,
+    bin_name: &str,
+    chrome: Option<&str>,
+) -> Result<String> {
+    let version_name = format!("chromedriver-{arch}");
+    let release = match chrome {
+        Some(chrome) => format!("LATEST_RELEASE_{}", major(chrome)),
+        None => "LATEST_RELEASE_STABLE".to_string(),
+    };
+
+    let version = reqwest::get(format!(
+        "https://googlechromelabs.github.io/chrome-for-testing/{release}"
+    ))
+    .await?
+    .error_for_status()
+    .context("No chromedriver was released for the installed Chrome")?
+    .text()
+    .await?
+    .trim()
+    .to_string();
+    let download_url = format!(
+        "https://storage.googleapis.com/chrome-for-testing-public/{version}/{arch}/{version_name}.zip"
+    );
+
+    let bytes = download(&download_url).await?;
+    extract_zip(&bytes, bin_name, bin_path)?;
+    Ok(version)
+}
+
+async fn download_geckodriver(bin_path: &Path, platform: &str, bin_name: &str) -> Result<()> {
+    // The latest release page redirects to the tag of the newest version.
+    let latest = reqwest::get("https://github.com/mozilla/geckodriver/releases/latest").await?;
+    let tag = latest
+        .url()
+        .path_segments()
+        .and_then(|mut segments| segments.next_back())
+        .filter(|tag| tag.starts_with('v'))
+        .context("Failed to find the latest geckodriver release")?
+        .to_string();
+    let extension = if platform.starts_with("win") {
+        "zip"
+    } else {
+        "tar.gz"
+    };
+    let download_url = format!(
+        "https://github.com/mozilla/geckodriver/releases/download/{tag}/geckodriver-{tag}-{platform}.{extension}"
+    );
+
+    let bytes = download(&download_url).await?;
+    if extension == "zip" {
+        extract_zip(&bytes, bin_name, bin_path)
+    } else {
+        extract_tar(&bytes, bin_name, bin_path)
+    }
+}
+
+/// Downloads `url`, failing on error statuses and truncated bodies.
+async fn download(url: &str) -> Result<Vec<u8>> {
+    let response = reqwest::get(url).await?.error_for_status()?;
+    let expected = response.content_length();
+    let bytes = response.bytes().await?;
+    if expected.is_some_and(|expected| expected != bytes.len() as u64) {
+        bail!("The download of {url} was interrupted");
+    }
+    Ok(bytes.to_vec())
+}
+
+/// Extracts the entry named `bin_name`, whatever its folder. Reading the whole
+/// entry checks its CRC, so a corrupted archive fails here.
+fn extract_zip(bytes: &[u8], bin_name: &str, bin_path: &Path) -> Result<()> {
+    let mut zip = zip::ZipArchive::new(std::io::Cursor::new(bytes))?;
+
+    for i in 0..zip.len() {
+        let mut file = zip.by_index(i)?;
+        if Path::new(file.name()).file_name() == Some(OsStr::new(bin_name)) {
+            let mut content = vec![];
+            std::io::copy(&mut file, &mut content)?;
+            std::fs::write(bin_path, content)?;
+            return Ok(());
+        }
+    }
+    bail!("{bin_name} not found in the archive")
+}
+
+fn extract_tar(bytes: &[u8], bin_name: &str, bin_path: &Path) -> Result<()> {
+    let mut archive = tar::Archive::new(GzDecoder::new(std::io::Cursor::new(bytes)));
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if entry.path()?.file_name() == Some(OsStr::new(bin_name)) {
+            entry.unpack(bin_path)?;
+            return Ok(());
+        }
+    }
+    bail!("{bin_name} not found in the archive")
+}
@@ -15,6 +15,13 @@ impl TranslatorObject {
             .property("language", land_data.language)
             .build()
     }
+
+    pub fn from_translator_data(translator_data: TranslatorData) -> Self {
+        Object::builder()
+            .property("code", translator_data.code)
+            .property("language", translator_data.language)
+            .build()
+    }
 }
 #[derive(Default, Clone)]
 pub struct TranslatorData {
@@ -36,6 +43,14 @@ impl TranslatorData {
         lang.unwrap()
     }
 
+    /// Source left for the provider to detect, for OCR languages it doesn't list.
+    pub fn auto() -> TranslatorData {
+        TranslatorData {
+            code: "auto".to_owned(),
+            language: "Detect Language".to_owned(),
+        }
+    }
+
     pub fn all_languages() -> [TranslatorData; 30] {
         [
             TranslatorData {
@@ -17,19 +17,12 @@ pub fn split_utf8(text: &str, start: usize, end: usize) -> String {
         .to_owned()
 }
 
-pub fn temp_path() -> Result<String> {
+/// Removes the captures older versions wrote to the temp folder before OCR,
+/// which were left behind when the app crashed.
+pub fn remove_temp_files() {
     let mut temp = env::temp_dir();
     temp.push(APP_ID);
-    std::fs::create_dir_all(&temp)?;
-    if let Some(path) = temp.to_str() {
-        return Ok(path.to_owned());
-    }
-    Err(anyhow::anyhow!("Failed to get temp path"))
-}
-
-pub fn remove_file(path: &str) -> Result<()> {
-    fs::remove_file(path)?;
-    Ok(())
+    let _ = fs::remove_dir_all(temp);
 }
 
 pub fn open_file(path: PathBuf) -> Result<fs::File> {
@@ -55,6 +48,18 @@ pub fn settings_path() -> Result<PathBuf> {
     Ok(path)
 }
 
+pub fn cache_path() -> Result<PathBuf> {
+    let mut path = system_path()?;
+    path.push("cache.json");
+    Ok(path)
+}
+
+pub fn usage_path() -> Result<PathBuf> {
+    let mut path = system_path()?;
+    path.push("usage.json");
+    Ok(path)
+}
+
 pub fn truncate_string(string: &str, size: usize) -> String {
     if string.is_char_boundary(size) {
         format!("{}...", split_utf8(string, 0, size - 3))
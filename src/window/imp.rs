@@ -3,7 +3,7 @@ use std::cell::RefCell;
 use crate::{
     profile_object::{ProfileData, ProfileObject},
     settings::Settings,
-    state, utils,
+    state, translation, utils,
 };
 use adw::subclass::prelude::*;
 use glib::subclass::InitializingObject;
@@ -11,6 +11,7 @@ use gtk::{gio, glib, prelude::ListModelExtManual, CompositeTemplate};
 use once_cell::sync::OnceCell;
 use std::fs;
 use tokio::process::Child;
+use tokio::sync::watch;
 // ANCHOR: struct
 // Object holding the state
 #[derive(CompositeTemplate, Default)]
@@ -18,6 +19,13 @@ use tokio::process::Child;
 pub struct Window {
     pub settings: RefCell<Settings>,
     pub webdriver: RefCell<Option<Child>>,
+    pub webdriver_checking: RefCell<bool>,
+    pub webdriver_failures: RefCell<u32>,
+    pub webdriver_restarts: RefCell<u32>,
+    /// Restarts in a row that didn't bring the webdriver back.
+    pub webdriver_crashes: RefCell<u32>,
+    /// Checks skipped before the next restart.
+    pub webdriver_backoff: RefCell<u32>,
     #[template_child]
     pub stack: TemplateChild<gtk::Stack>,
     #[template_child]
@@ -27,21 +35,43 @@ pub struct Window {
     #[template_child]
     pub dd_translation: TemplateChild<gtk::DropDown>,
     #[template_child]
+    pub ollama_box: TemplateChild<gtk::Box>,
+    #[template_child]
+    pub dd_model: TemplateChild<gtk::DropDown>,
+    #[template_child]
+    pub spin_temperature: TemplateChild<gtk::SpinButton>,
+    #[template_child]
+    pub entry_keep_alive: TemplateChild<gtk::Entry>,
+    #[template_child]
     pub chk_full_screen: TemplateChild<gtk::CheckButton>,
     #[template_child]
+    pub chk_paragraphs: TemplateChild<gtk::CheckButton>,
+    #[template_child]
+    pub spin_context: TemplateChild<gtk::SpinButton>,
+    #[template_child]
+    pub spin_confidence: TemplateChild<gtk::SpinButton>,
+    #[template_child]
     pub action_button: TemplateChild<gtk::Button>,
     #[template_child]
     pub remove_button: TemplateChild<gtk::Button>,
     #[template_child]
     pub status_label: TemplateChild<gtk::EditableLabel>,
     #[template_child]
+    pub usage_label: TemplateChild<gtk::Label>,
+    #[template_child]
+    pub webdriver_label: TemplateChild<gtk::Label>,
+    #[template_child]
     pub title: TemplateChild<gtk::Entry>,
     #[template_child]
     pub config_button: TemplateChild<gtk::Button>,
     #[template_child]
+    pub menu_button: TemplateChild<gtk::MenuButton>,
+    #[template_child]
     pub profiles_list: TemplateChild<gtk::ListBox>,
     pub profiles: OnceCell<gio::ListStore>,
     pub running: RefCell<bool>,
+    /// Tells the translation thread to stop without waiting for its request.
+    pub cancel: RefCell<Option<watch::Sender<bool>>>,
     pub state: RefCell<state::State>,
     pub drawing_area: gtk::DrawingArea,
     pub use_areas: gtk::Switch,
@@ -73,11 +103,14 @@ impl ObjectImpl for Window {
         self.parent_constructed();
         // Setup
         let obj = self.obj();
+        utils::remove_temp_files();
         obj.setup_webdriver();
+        obj.supervise_webdriver();
         obj.setup_settings();
         obj.setup_data();
         obj.setup_actions();
         obj.setup_drag_action();
+        obj.setup_area_editor();
         obj.setup_profiles();
         let _ = obj.restore_data();
     }
@@ -92,10 +125,6 @@ impl WidgetImpl for Window {}
 impl WindowImpl for Window {
     fn close_request(&self) -> glib::Propagation {
         let obj = self.obj();
-        if let Ok(path) = utils::temp_path() {
-            fs::remove_dir_all(path).expect("Could not remove temp files");
-        }
-
         let webdriver = obj.imp().webdriver.take();
         if let Some(mut webdriver) = webdriver {
             let _ = webdriver.start_kill();
@@ -120,6 +149,8 @@ impl WindowImpl for Window {
         }
 
         let _ = self.obj().settings().update_json();
+        let _ = translation::cache::save();
+        let _ = translation::limiter::save();
 
         self.parent_close_request()
     }
@@ -2,13 +2,22 @@ mod imp;
 
 use crate::{
     area_object::{AreaData, AreaObject},
-    ocr_object::{OcrData, OcrObject},
+    cleanup,
+    glossary::{self, Glossary, GlossaryEntry, Protected},
+    image_hash::ImageHash,
+    ocr_object::{OcrData, OcrObject, OcrOptions},
     paint,
+    preprocess::{Binarize, Preprocess},
     profile_object::{ProfileData, ProfileObject},
+    rewrite::{RewriteRule, Rewriter},
     screen_object::{ScreenData, ScreenObject},
     settings::Settings,
     state::State,
-    translation,
+    translation::{
+        self, deepl_api,
+        webdriver::{self, Backend, Browser},
+        Chain, DeepLApi, Line, Ollama, TimedOut, Translator,
+    },
     translator_object::{TranslatorData, TranslatorObject},
     utils,
     window_manager::sys::WindowManager,
@@ -19,8 +28,15 @@ use anyhow::{Context, Result};
 use gio::{ListStore, SimpleAction};
 use glib::{clone, Object};
 use gtk::{gio, glib, pango, Expression, PropertyExpression};
-use std::{cell::RefMut, thread};
-use tokio::sync::mpsc;
+use std::{
+    cell::{RefCell, RefMut},
+    collections::VecDeque,
+    future::Future,
+    path::PathBuf,
+    rc::Rc,
+    thread,
+};
+use tokio::sync::{mpsc, watch};
 use tokio::time::{sleep, Duration};
 
 glib::wrapper! {
@@ -33,6 +49,24 @@ glib::wrapper! {
 const WINDOW_NAME: &str = "GT Overlay";
 const PORT: u32 = 50682;
 const TRANSLATION_DELAY: u64 = 3000;
+const MAX_STABLE_CAPTURES: u32 = 10;
+const WEBDRIVER_CHECK_INTERVAL: u32 = 5;
+const WEBDRIVER_MAX_FAILURES: u32 = 2;
+const WEBDRIVER_MAX_RESTARTS: u32 = 5;
+
+/// What the translation thread hands to the overlay after each capture.
+enum Frame {
+    Translated(Vec<AreaData>),
+    /// Nothing to draw, the reason goes to the status label.
+    Skipped(String),
+}
+
+/// Provider listed in the fallback preferences, tried only while `enabled`.
+struct Fallback {
+    id: String,
+    name: String,
+    enabled: bool,
+}
 
 impl Window {
     pub fn new(app: &adw::Application) -> Self {
@@ -45,45 +79,244 @@ impl Window {
             #[weak(rename_to = window)]
             self,
             async move {
-                let rt = tokio::runtime::Runtime::new().unwrap();
-                rt.block_on(async {
-                    if !translation::webdriver_path().unwrap().exists() {
-                        let dialog = gtk::Window::builder()
-                            .title("Installing webdriver...")
-                            .modal(true)
-                            .resizable(false)
-                            .decorated(true)
-                            .default_width(300)
-                            .default_height(150)
-                            .build();
-
-                        let label = gtk::Label::new(Some(
-                            "This message will close altomatic when finished",
-                        ));
-                        dialog.set_child(Some(&label));
-                        dialog.present();
-
-                        if let Err(err) = translation::download_webdriver().await {
-                            window.dialog("Error downloading webdriver", &err.to_string());
-                            return;
-                        }
-                        dialog.close();
-                        window.dialog(
-                            "Webdriver Installed",
-                            "Webdriver installed and program ready to be used",
-                        );
+                let (needs_webdriver, browser) = {
+                    let settings = window.settings();
+                    let needs_webdriver = settings.tra_chain().into_iter().any(|id| {
+                        translation::provider(id, &settings)
+                            .capabilities()
+                            .webdriver
+                    });
+                    (needs_webdriver, Browser::new(&settings))
+                };
+                if !needs_webdriver || window.imp().webdriver.borrow().is_some() {
+                    return;
+                }
+                if !webdriver::webdriver_path(&browser).is_ok_and(|path| path.exists()) {
+                    if !window.install_webdriver(&browser, None).await {
+                        return;
                     }
-                });
-                match translation::run_webdriver(PORT) {
-                    Ok(child) => {
-                        let _ = window.imp().webdriver.replace(Some(child));
+                } else if let Some(mismatch) = webdriver::version_mismatch(&browser) {
+                    let dialog = gtk::AlertDialog::builder()
+                        .message("Webdriver Version Mismatch")
+                        .detail(mismatch)
+                        .buttons(["Ignore", "Reinstall"])
+                        .cancel_button(0)
+                        .default_button(1)
+                        .modal(true)
+                        .build();
+                    if dialog.choose_future(Some(&window)).await == Ok(1)
+                        && !window.install_webdriver(&browser, None).await
+                    {
+                        return;
                     }
-                    Err(err) => {
-                        window.dialog("error running webdriver", &err.to_string());
+                }
+                window.reset_webdriver_supervision();
+                window.run_webdriver(&browser);
+            }
+        ));
+    }
+
+    fn run_webdriver(&self, browser: &Browser) {
+        match webdriver::run_webdriver(browser, PORT) {
+            Ok(child) => {
+                let _ = self.imp().webdriver.replace(Some(child));
+            }
+            Err(err) => {
+                self.dialog("error running webdriver", &err.to_string());
+            }
+        }
+    }
+
+    /// Downloads the webdriver, or installs it from `archive` when given.
+    async fn install_webdriver(&self, browser: &Browser, archive: Option<PathBuf>) -> bool {
+        let dialog = gtk::Window::builder()
+            .title("Installing webdriver...")
+            .modal(true)
+            .transient_for(self)
+            .resizable(false)
+            .decorated(true)
+            .default_width(300)
+            .default_height(150)
+            .build();
+
+        let label = gtk::Label::new(Some("This message will close altomatic when finished"));
+        dialog.set_child(Some(&label));
+        dialog.present();
+
+        let task_browser = browser.clone();
+        let mut rx = spawn_task(async move {
+            match archive {
+                Some(archive) => webdriver::install_webdriver_from(&task_browser, &archive),
+                None => webdriver::download_webdriver(&task_browser).await,
+            }
+        });
+        let result = rx.recv().await;
+        dialog.close();
+
+        match result {
+            Some(Ok(version)) => {
+                self.dialog(
+                    "Webdriver Installed",
+                    &format!("Webdriver {version} installed and program ready to be used"),
+                );
+                true
+            }
+            Some(Err(err)) => {
+                self.dialog("Error installing webdriver", &err.to_string());
+                false
+            }
+            None => false,
+        }
+    }
+
+    /// Stops the running webdriver, installs it again and starts it.
+    fn reinstall_webdriver(&self, archive: Option<PathBuf>) {
+        let webdriver = self.imp().webdriver.take();
+        glib::spawn_future_local(clone!(
+            #[weak(rename_to = window)]
+            self,
+            async move {
+                if let Some(mut webdriver) = webdriver {
+                    // The executable can't be replaced while it runs on Windows.
+                    let _ = spawn_task(async move { webdriver.kill().await })
+                        .recv()
+                        .await;
+                }
+                let browser = Browser::new(&window.settings());
+                if window.install_webdriver(&browser, archive).await {
+                    window.reset_webdriver_supervision();
+                    window.run_webdriver(&browser);
+                }
+            }
+        ));
+    }
+
+    /// Checks the running webdriver every few seconds and restarts it when the
+    /// process exits or stops answering.
+    fn supervise_webdriver(&self) {
+        glib::timeout_add_seconds_local(
+            WEBDRIVER_CHECK_INTERVAL,
+            clone!(
+                #[weak(rename_to = window)]
+                self,
+                #[upgrade_or]
+                glib::ControlFlow::Break,
+                move || {
+                    window.check_webdriver();
+                    glib::ControlFlow::Continue
+                }
+            ),
+        );
+    }
+
+    fn check_webdriver(&self) {
+        let obj = self.imp();
+        if *obj.webdriver_checking.borrow() {
+            return;
+        }
+        let backoff = *obj.webdriver_backoff.borrow();
+        if backoff > 0 {
+            obj.webdriver_backoff.replace(backoff - 1);
+            return;
+        }
+        let exited = match obj.webdriver.borrow_mut().as_mut() {
+            Some(webdriver) => !matches!(webdriver.try_wait(), Ok(None)),
+            None => return,
+        };
+        if exited {
+            self.restart_crashed_webdriver("the process exited");
+            return;
+        }
+
+        obj.webdriver_checking.replace(true);
+        let mut rx = spawn_task(webdriver::webdriver_status(PORT));
+        glib::spawn_future_local(clone!(
+            #[weak(rename_to = window)]
+            self,
+            async move {
+                let status = rx.recv().await;
+                let obj = window.imp();
+                obj.webdriver_checking.replace(false);
+                match status {
+                    Some(Ok(())) => {
+                        obj.webdriver_failures.replace(0);
+                        obj.webdriver_crashes.replace(0);
+                    }
+                    Some(Err(err)) => {
+                        let failures = *obj.webdriver_failures.borrow() + 1;
+                        obj.webdriver_failures.replace(failures);
+                        // A single slow answer isn't enough to kill it.
+                        if failures >= WEBDRIVER_MAX_FAILURES {
+                            window.restart_crashed_webdriver(&err.to_string());
+                        }
                     }
+                    None => {}
+                }
+            }
+        ));
+    }
+
+    fn restart_crashed_webdriver(&self, reason: &str) {
+        let obj = self.imp();
+        obj.webdriver_failures.replace(0);
+        let crashes = *obj.webdriver_crashes.borrow() + 1;
+        obj.webdriver_crashes.replace(crashes);
+        let webdriver = obj.webdriver.take();
+        if crashes > WEBDRIVER_MAX_RESTARTS {
+            // Without a webdriver the checks stop until it is started again.
+            if let Some(mut webdriver) = webdriver {
+                let _ = webdriver.start_kill();
+            }
+            obj.webdriver_label.set_text(&format!(
+                "Webdriver stopped after {WEBDRIVER_MAX_RESTARTS} failed restarts: {reason}. \
+                 Reinstall it from the preferences"
+            ));
+            obj.webdriver_label.set_visible(true);
+            return;
+        }
+        // Each restart that didn't help waits twice as long as the previous one.
+        obj.webdriver_backoff.replace((1 << (crashes - 1)) - 1);
+        let restarts = *obj.webdriver_restarts.borrow() + 1;
+        obj.webdriver_restarts.replace(restarts);
+
+        glib::spawn_future_local(clone!(
+            #[weak(rename_to = window)]
+            self,
+            async move {
+                if let Some(mut webdriver) = webdriver {
+                    // Wait for the port to be released before starting again.
+                    let _ = spawn_task(async move { webdriver.kill().await })
+                        .recv()
+                        .await;
                 }
+                let browser = Browser::new(&window.settings());
+                window.run_webdriver(&browser);
             }
         ));
+
+        let time = glib::DateTime::now_local()
+            .and_then(|now| now.format("%H:%M:%S"))
+            .map(|time| time.to_string())
+            .unwrap_or_default();
+        obj.webdriver_label.set_text(&format!(
+            "Webdriver restarted {restarts} time(s), last at {time}: {reason}"
+        ));
+        obj.webdriver_label.set_visible(true);
+    }
+
+    /// Forgets the failed restarts, for a webdriver started by the user.
+    fn reset_webdriver_supervision(&self) {
+        let obj = self.imp();
+        obj.webdriver_crashes.replace(0);
+        obj.webdriver_backoff.replace(0);
+    }
+
+    /// Stops the running webdriver and starts the one now selected.
+    fn restart_webdriver(&self) {
+        if let Some(mut webdriver) = self.imp().webdriver.take() {
+            let _ = webdriver.start_kill();
+        }
+        self.setup_webdriver();
     }
 
     fn setup_settings(&self) {
@@ -182,6 +415,10 @@ impl Window {
                     .imp()
                     .config_button
                     .set_sensitive(!button.is_active());
+                window
+                    .imp()
+                    .chk_paragraphs
+                    .set_sensitive(button.is_active());
 
                 if let Ok(profile) = window.selected_profile() {
                     profile.set_use_areas(button.is_active());
@@ -189,6 +426,16 @@ impl Window {
             }
         ));
 
+        obj.chk_paragraphs.connect_toggled(clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |button| {
+                if let Ok(profile) = window.selected_profile() {
+                    profile.set_paragraphs(button.is_active());
+                }
+            }
+        ));
+
         obj.dd_translation.connect_selected_item_notify(clone!(
             #[weak(rename_to = window)]
             self,
@@ -204,6 +451,59 @@ impl Window {
             }
         ));
 
+        obj.dd_model.connect_selected_item_notify(clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |drop_down| {
+                let model = drop_down
+                    .selected_item()
+                    .and_downcast::<gtk::StringObject>();
+                if let (Some(model), Ok(profile)) = (model, window.selected_profile()) {
+                    profile.set_ollama_model(model.string().to_string());
+                }
+            }
+        ));
+
+        obj.spin_temperature.connect_value_changed(clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |spin| {
+                if let Ok(profile) = window.selected_profile() {
+                    profile.set_ollama_temperature(spin.value());
+                }
+            }
+        ));
+
+        obj.spin_context.connect_value_changed(clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |spin| {
+                if let Ok(profile) = window.selected_profile() {
+                    profile.set_context_lines(spin.value() as u32);
+                }
+            }
+        ));
+
+        obj.spin_confidence.connect_value_changed(clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |spin| {
+                if let Ok(profile) = window.selected_profile() {
+                    profile.set_min_confidence(spin.value() as u32);
+                }
+            }
+        ));
+
+        obj.entry_keep_alive.connect_changed(clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |entry| {
+                if let Ok(profile) = window.selected_profile() {
+                    profile.set_ollama_keep_alive(entry.text().to_string());
+                }
+            }
+        ));
+
         obj.dd_ocr.connect_selected_item_notify(clone!(
             #[weak(rename_to = window)]
             self,
@@ -283,6 +583,51 @@ impl Window {
             ),
         );
 
+        self.add_simple_action(
+            "preferences",
+            clone!(
+                #[weak(rename_to = window)]
+                self,
+                move |_, _| window.open_preferences()
+            ),
+        );
+
+        self.add_simple_action(
+            "glossary",
+            clone!(
+                #[weak(rename_to = window)]
+                self,
+                move |_, _| window.open_glossary()
+            ),
+        );
+
+        self.add_simple_action(
+            "ocr-cleanup",
+            clone!(
+                #[weak(rename_to = window)]
+                self,
+                move |_, _| window.open_cleanup()
+            ),
+        );
+
+        self.add_simple_action(
+            "rewrite-rules",
+            clone!(
+                #[weak(rename_to = window)]
+                self,
+                move |_, _| window.open_rewrite_rules()
+            ),
+        );
+
+        self.add_simple_action(
+            "translation-cache",
+            clone!(
+                #[weak(rename_to = window)]
+                self,
+                move |_, _| window.open_cache()
+            ),
+        );
+
         self.add_simple_action(
             "refresh-windows",
             clone!(
@@ -336,82 +681,918 @@ impl Window {
                     let str_value = new_value.str().unwrap();
                     let _ = window.settings().set("tra-provider", str_value.to_string());
                     action.set_state(&new_value);
+                    window.setup_webdriver();
+                    window.setup_dd_translation();
+                    window.setup_dd_model();
                 }
             ),
         )
     }
 
-    fn navigate(&self, page: &str) {
-        self.imp().stack.set_visible_child_name(page);
+    fn setup_menu(&self) {
+        let providers = gio::Menu::new();
+        for translator in translation::registry(&self.settings()) {
+            providers.append(
+                Some(&format!("Use {}", translator.name())),
+                Some(&format!("win.toggle-language::{}", translator.id())),
+            );
+        }
+        let menu = self
+            .imp()
+            .menu_button
+            .menu_model()
+            .and_downcast::<gio::Menu>()
+            .expect("The main menu needs to be a `gio::Menu`.");
+        menu.prepend_section(None, &providers);
+    }
+
+    fn navigate(&self, page: &str) {
+        self.imp().stack.set_visible_child_name(page);
+    }
+
+    fn setup_data(&self) {
+        self.setup_menu();
+        self.setup_dd_ocr();
+        self.setup_dd_translation();
+        self.setup_dd_model();
+        let _ = self.setup_dd_screen();
+
+        self.navigate("main");
+    }
+
+    fn setup_dd_ocr(&self) {
+        let languages = rusty_tesseract::get_tesseract_langs();
+        match languages {
+            Ok(values) => {
+                let list = ListStore::new::<OcrObject>();
+                for lang in &values {
+                    list.append(&OcrObject::new(lang.to_string()));
+                }
+                let expression = PropertyExpression::new(
+                    OcrObject::static_type(),
+                    Expression::NONE,
+                    "language"
+                );
+                self.imp().dd_ocr.set_expression(Some(expression));
+                self.imp().dd_ocr.set_model(Some(&list));
+            }
+            Err(value) =>
+                self.dialog(
+                    "Can't find languages for translation",
+                    &format!("{value}\r\nPossible cause of the problem: Tesseract is not installed in your system. Please follow the instructions at https://tesseract-ocr.github.io/tessdoc/Installation.html")
+                ),
+        }
+    }
+
+    fn setup_dd_translation(&self) {
+        let translator = {
+            let settings = self.settings();
+            translation::provider(settings.tra_provider(), &settings)
+        };
+        let mut rx = spawn_task(async move { translator.languages().await });
+
+        glib::spawn_future_local(clone!(
+            #[weak(rename_to = window)]
+            self,
+            async move {
+                let languages = match rx.recv().await {
+                    Some(Ok(value)) => value,
+                    Some(Err(err)) => {
+                        window.dialog("Can't load the provider languages", &err.to_string());
+                        vec![]
+                    }
+                    None => return,
+                };
+                let code = match window.selected_profile() {
+                    Ok(profile) => profile.translation(),
+                    Err(_) => window.settings().tra_lang().to_string(),
+                };
+
+                let list = ListStore::new::<TranslatorObject>();
+                list.append(&TranslatorObject::new("nt".to_string()));
+                for lang in languages {
+                    list.append(&TranslatorObject::from_translator_data(lang));
+                }
+                let expression = PropertyExpression::new(
+                    TranslatorObject::static_type(),
+                    Expression::NONE,
+                    "language",
+                );
+                let obj = window.imp();
+                obj.dd_translation.set_expression(Some(expression));
+                obj.dd_translation.set_model(Some(&list));
+                window.select_translation(&code);
+            }
+        ));
+    }
+
+    fn select_translation(&self, code: &str) {
+        let dd_translation = &self.imp().dd_translation;
+        let Some(model) = dd_translation.model() else {
+            return;
+        };
+        let id = (0..model.n_items())
+            .find(|i| {
+                model
+                    .item(*i)
+                    .and_downcast::<TranslatorObject>()
+                    .is_some_and(|lang| lang.code() == code)
+            })
+            .unwrap_or(0);
+        dd_translation.set_selected(id);
+    }
+
+    fn setup_dd_model(&self) {
+        let settings = self.settings().clone();
+        let is_ollama = settings.tra_provider() == Ollama::new(&settings).id();
+        self.imp().ollama_box.set_visible(is_ollama);
+        if !is_ollama {
+            return;
+        }
+
+        let mut rx = spawn_task(async move { Ollama::new(&settings).models().await });
+        glib::spawn_future_local(clone!(
+            #[weak(rename_to = window)]
+            self,
+            async move {
+                let models = match rx.recv().await {
+                    Some(Ok(value)) => value,
+                    Some(Err(err)) => {
+                        window.dialog("Can't load the Ollama models", &err.to_string());
+                        vec![]
+                    }
+                    None => return,
+                };
+                let name = window
+                    .selected_profile()
+                    .map(|profile| profile.ollama_model())
+                    .unwrap_or_default();
+
+                let models = models.iter().map(String::as_str).collect::<Vec<&str>>();
+                window
+                    .imp()
+                    .dd_model
+                    .set_model(Some(&gtk::StringList::new(&models)));
+                window.select_model(&name);
+            }
+        ));
+    }
+
+    fn select_model(&self, name: &str) {
+        let dd_model = &self.imp().dd_model;
+        let Some(model) = dd_model.model() else {
+            return;
+        };
+        let id = (0..model.n_items())
+            .find(|i| {
+                model
+                    .item(*i)
+                    .and_downcast::<gtk::StringObject>()
+                    .is_some_and(|item| item.string() == name)
+            })
+            .unwrap_or(0);
+        dd_model.set_selected(id);
+    }
+
+    fn setup_dd_screen(&self) -> Result<()> {
+        let list = open_windows()?;
+
+        let expression =
+            PropertyExpression::new(ScreenObject::static_type(), Expression::NONE, "title");
+
+        self.imp().dd_screen.set_expression(Some(expression));
+        self.imp().dd_screen.set_model(Some(&list));
+
+        Ok(())
+    }
+
+    fn dialog(&self, message: &str, detail: &str) {
+        let dialog = gtk::AlertDialog::builder()
+            .detail(detail)
+            .message(message)
+            .modal(true)
+            .build();
+        dialog.show(Some(self))
+    }
+
+    // region: Preferences
+    fn open_preferences(&self) {
+        let settings = self.settings().clone();
+        let browser = Browser::new(&settings);
+
+        let driver = adw::PreferencesGroup::builder()
+            .title("Webdriver")
+            .description("Browser used by the Google and DeepL web providers")
+            .build();
+        driver.add(&self.backend_row(settings.webdriver_backend()));
+        driver.add(&self.entry_row("Driver Path", "webdriver-path", settings.webdriver_path()));
+        driver.add(&self.entry_row("Browser Path", "browser-path", settings.browser_path()));
+        driver.add(&self.install_row(&browser));
+
+        let libre = adw::PreferencesGroup::builder()
+            .title("LibreTranslate")
+            .description("Server used by the LibreTranslate provider")
+            .build();
+        libre.add(&self.entry_row("Server URL", "libre-url", settings.libre_url()));
+        libre.add(&self.password_row("API Key", "libre-key", settings.libre_key()));
+
+        let deepl = adw::PreferencesGroup::builder()
+            .title("DeepL API")
+            .description("Free keys end with \":fx\" and use the free API host")
+            .build();
+        deepl.add(&self.password_row("Authentication Key", "deepl-key", settings.deepl_key()));
+        deepl.add(&self.formality_row(settings.deepl_formality()));
+        deepl.add(&self.usage_row(Box::new(DeepLApi::new(&settings))));
+
+        let openai = adw::PreferencesGroup::builder()
+            .title("OpenAI Compatible")
+            .description("Any /chat/completions endpoint, like llama.cpp or vLLM")
+            .build();
+        openai.add(&self.entry_row("Base URL", "openai-url", settings.openai_url()));
+        openai.add(&self.password_row("API Key", "openai-key", settings.openai_key()));
+        openai.add(&self.entry_row("Model", "openai-model", settings.openai_model()));
+        openai.add(&self.text_row(
+            "Prompt",
+            "Placeholders: {source}, {target}, {context} and {text}",
+            "llm-prompt",
+            settings.llm_prompt(),
+        ));
+
+        let ollama = adw::PreferencesGroup::builder()
+            .title("Ollama")
+            .description("Model, temperature and keep alive are chosen per profile")
+            .build();
+        ollama.add(&self.entry_row("Server URL", "ollama-url", settings.ollama_url()));
+
+        let command = adw::PreferencesGroup::builder()
+            .title("External Command")
+            .description(
+                "Receives one JSON request per line and answers with one JSON line. \
+                 Arguments with spaces go in quotes",
+            )
+            .build();
+        command.add(&self.entry_row("Executable", "command-path", settings.command_path()));
+        command.add(&self.entry_row("Arguments", "command-args", settings.command_args()));
+
+        let fallback = adw::PreferencesGroup::builder()
+            .title("Fallback")
+            .description(
+                "Providers tried after the selected one fails. \
+                 Each provider is retried with a delay that doubles on every attempt",
+            )
+            .build();
+        let registry = translation::registry(&settings);
+        let mut providers = vec![];
+        for id in settings.tra_fallback() {
+            if let Some(translator) = registry.iter().find(|translator| translator.id() == id) {
+                providers.push(Fallback {
+                    id: id.clone(),
+                    name: translator.name().to_string(),
+                    enabled: true,
+                });
+            }
+        }
+        for translator in &registry {
+            if !providers
+                .iter()
+                .any(|provider| provider.id == translator.id())
+            {
+                providers.push(Fallback {
+                    id: translator.id().to_string(),
+                    name: translator.name().to_string(),
+                    enabled: false,
+                });
+            }
+        }
+        let providers = Rc::new(RefCell::new(providers));
+        let fallback_list = gtk::ListBox::builder()
+            .selection_mode(gtk::SelectionMode::None)
+            .build();
+        fill_list(&fallback_list, &providers, fallback_fields, true, false);
+        let fallback_row = adw::ExpanderRow::builder()
+            .title("Fallback Providers")
+            .subtitle("Checked providers are tried from top to bottom")
+            .build();
+        fallback_row.add_row(&fallback_list);
+        fallback.add(&fallback_row);
+        fallback.add(&self.spin_row(
+            "Retry Delay",
+            "Milliseconds before the first retry",
+            "retry-delay".to_string(),
+            settings.retry_delay() as f64,
+            100.0..=10_000.0,
+            100.0,
+        ));
+        let retries = adw::ExpanderRow::builder()
+            .title("Retries")
+            .subtitle("Attempts made after the first failure")
+            .build();
+        for translator in translation::registry(&settings) {
+            retries.add_row(&self.spin_row(
+                translator.name(),
+                translator.id(),
+                format!("{}-retries", translator.id()),
+                settings.retries(translator.id()) as f64,
+                0.0..=10.0,
+                1.0,
+            ));
+        }
+        fallback.add(&retries);
+        let timeouts = adw::ExpanderRow::builder()
+            .title("Timeouts")
+            .subtitle("Seconds a request may take before it is retried")
+            .build();
+        for translator in translation::registry(&settings) {
+            timeouts.add_row(&self.spin_row(
+                translator.name(),
+                translator.id(),
+                format!("{}-timeout", translator.id()),
+                settings.timeout(translator.id()) as f64,
+                1.0..=600.0,
+                1.0,
+            ));
+        }
+        fallback.add(&timeouts);
+
+        let limits = adw::PreferencesGroup::builder()
+            .title("Limits")
+            .description("Zero means unlimited")
+            .build();
+        for translator in translation::registry(&settings) {
+            let id = translator.id();
+            let provider_limits = settings.limits(id);
+            let row = adw::ExpanderRow::builder()
+                .title(translator.name())
+                .subtitle(format!(
+                    "{} characters this month",
+                    translation::limiter::used(id)
+                ))
+                .build();
+            row.add_row(&self.spin_row(
+                "Requests per Second",
+                "Requests beyond it wait for their turn",
+                format!("{id}-rate"),
+                provider_limits.requests_per_second,
+                0.0..=100.0,
+                0.1,
+            ));
+            row.add_row(&self.spin_row(
+                "Monthly Characters",
+                "Requests beyond it go to the next provider",
+                format!("{id}-quota"),
+                provider_limits.monthly_characters as f64,
+                0.0..=100_000_000.0,
+                1000.0,
+            ));
+            limits.add(&row);
+        }
+
+        let page = adw::PreferencesPage::builder()
+            .title("Providers")
+            .icon_name("preferences-system-symbolic")
+            .build();
+        page.add(&fallback);
+        page.add(&limits);
+        page.add(&driver);
+        page.add(&libre);
+        page.add(&deepl);
+        page.add(&openai);
+        page.add(&ollama);
+        page.add(&command);
+
+        let preferences = adw::PreferencesWindow::builder()
+            .title("Preferences")
+            .modal(true)
+            .transient_for(self)
+            .build();
+        preferences.add(&page);
+        preferences.connect_close_request(clone!(
+            #[weak(rename_to = window)]
+            self,
+            #[upgrade_or]
+            glib::Propagation::Proceed,
+            move |_| {
+                window.save_fallback(&providers.borrow());
+                window.setup_dd_translation();
+                window.setup_dd_model();
+                if Browser::new(&window.settings()) != browser {
+                    window.restart_webdriver();
+                } else {
+                    window.setup_webdriver();
+                }
+                glib::Propagation::Proceed
+            }
+        ));
+        preferences.present();
+    }
+
+    fn entry_row(&self, title: &str, prop: &'static str, value: &str) -> adw::EntryRow {
+        let row = adw::EntryRow::builder().title(title).build();
+        row.set_text(value);
+        self.bind_setting(&row, prop);
+        row
+    }
+
+    fn password_row(&self, title: &str, prop: &'static str, value: &str) -> adw::PasswordEntryRow {
+        let row = adw::PasswordEntryRow::builder().title(title).build();
+        row.set_text(value);
+        self.bind_setting(&row, prop);
+        row
+    }
+
+    fn text_row(
+        &self,
+        title: &str,
+        subtitle: &str,
+        prop: &'static str,
+        value: &str,
+    ) -> adw::ExpanderRow {
+        let buffer = gtk::TextBuffer::new(None);
+        buffer.set_text(value);
+        buffer.connect_changed(clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |buffer| {
+                let text = buffer.text(&buffer.start_iter(), &buffer.end_iter(), false);
+                let _ = window.settings().set(prop, text.to_string());
+            }
+        ));
+        let text_view = gtk::TextView::builder()
+            .buffer(&buffer)
+            .wrap_mode(gtk::WrapMode::WordChar)
+            .height_request(120)
+            .top_margin(6)
+            .bottom_margin(6)
+            .left_margin(6)
+            .right_margin(6)
+            .build();
+        let row = adw::ExpanderRow::builder()
+            .title(title)
+            .subtitle(subtitle)
+            .build();
+        row.add_row(&text_view);
+        row
+    }
+
+    fn spin_row(
+        &self,
+        title: &str,
+        subtitle: &str,
+        prop: String,
+        value: f64,
+        range: std::ops::RangeInclusive<f64>,
+        step: f64,
+    ) -> adw::ActionRow {
+        let spin = gtk::SpinButton::with_range(*range.start(), *range.end(), step);
+        spin.set_valign(gtk::Align::Center);
+        spin.set_value(value);
+        spin.connect_value_changed(clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |spin| {
+                let _ = window.settings().set(&prop, spin.value().to_string());
+            }
+        ));
+        let row = adw::ActionRow::builder()
+            .title(title)
+            .subtitle(subtitle)
+            .build();
+        row.add_suffix(&spin);
+        row
+    }
+
+    fn install_row(&self, browser: &Browser) -> adw::ActionRow {
+        let row = adw::ActionRow::builder()
+            .title("Installed Version")
+            .subtitle(
+                webdriver::installed_version(browser)
+                    .unwrap_or_else(|| "Not installed".to_string()),
+            )
+            .build();
+
+        let reinstall = gtk::Button::builder()
+            .label("Reinstall")
+            .tooltip_text("Download the webdriver matching the installed browser")
+            .valign(gtk::Align::Center)
+            .build();
+        reinstall.connect_clicked(clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_| window.reinstall_webdriver(None)
+        ));
+
+        let from_file = gtk::Button::builder()
+            .label("Install from File")
+            .tooltip_text("Install from a downloaded zip, tar.gz or executable")
+            .valign(gtk::Align::Center)
+            .build();
+        from_file.connect_clicked(clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |button| {
+                let parent = button.root().and_downcast::<gtk::Window>();
+                gtk::FileDialog::builder()
+                    .title("Install Webdriver")
+                    .build()
+                    .open(
+                        parent.as_ref(),
+                        gio::Cancellable::NONE,
+                        clone!(
+                            #[weak]
+                            window,
+                            move |file| {
+                                if let Some(path) = file.ok().and_then(|file| file.path()) {
+                                    window.reinstall_webdriver(Some(path));
+                                }
+                            }
+                        ),
+                    );
+            }
+        ));
+
+        row.add_suffix(&from_file);
+        row.add_suffix(&reinstall);
+        row
+    }
+
+    fn backend_row(&self, value: Backend) -> adw::ComboRow {
+        let labels = Backend::ALL
+            .iter()
+            .map(|backend| backend.name())
+            .collect::<Vec<&str>>();
+        let row = adw::ComboRow::builder()
+            .title("Backend")
+            .subtitle("Driver paths left empty are downloaded automatically")
+            .model(&gtk::StringList::new(&labels))
+            .build();
+        let selected = Backend::ALL
+            .iter()
+            .position(|backend| *backend == value)
+            .unwrap_or(0);
+        row.set_selected(selected as u32);
+        row.connect_selected_notify(clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |row| {
+                if let Some(backend) = Backend::ALL.get(row.selected() as usize) {
+                    let _ = window
+                        .settings()
+                        .set("webdriver-backend", backend.id().to_string());
+                }
+            }
+        ));
+        row
+    }
+
+    fn formality_row(&self, value: &str) -> adw::ComboRow {
+        let labels = deepl_api::FORMALITIES
+            .iter()
+            .map(|(_, label)| *label)
+            .collect::<Vec<&str>>();
+        let row = adw::ComboRow::builder()
+            .title("Formality")
+            .model(&gtk::StringList::new(&labels))
+            .build();
+        let selected = deepl_api::FORMALITIES
+            .iter()
+            .position(|(formality, _)| *formality == value)
+            .unwrap_or(0);
+        row.set_selected(selected as u32);
+        row.connect_selected_notify(clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |row| {
+                if let Some((formality, _)) = deepl_api::FORMALITIES.get(row.selected() as usize) {
+                    let _ = window
+                        .settings()
+                        .set("deepl-formality", formality.to_string());
+                }
+            }
+        ));
+        row
+    }
+
+    fn usage_row(&self, translator: Box<dyn Translator>) -> adw::ActionRow {
+        let row = adw::ActionRow::builder()
+            .title("Usage")
+            .subtitle("Loading...")
+            .build();
+        let mut rx = spawn_task(async move { translator.usage().await });
+        glib::spawn_future_local(clone!(
+            #[weak]
+            row,
+            async move {
+                let subtitle = match rx.recv().await {
+                    Some(Ok(Some(usage))) => {
+                        format!("{} of {} characters", usage.characters, usage.limit)
+                    }
+                    Some(Ok(None)) | None => "Not reported".to_string(),
+                    Some(Err(err)) => err.to_string(),
+                };
+                row.set_subtitle(&subtitle);
+            }
+        ));
+        row
+    }
+
+    fn save_fallback(&self, providers: &[Fallback]) {
+        let ids = providers
+            .iter()
+            .filter(|provider| provider.enabled)
+            .map(|provider| provider.id.as_str())
+            .collect::<Vec<&str>>();
+        let _ = self.settings().set("tra-fallback", ids.join(","));
+    }
+
+    fn bind_setting(&self, editable: &impl IsA<gtk::Editable>, prop: &'static str) {
+        editable.connect_changed(clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |editable| {
+                let _ = window.settings().set(prop, editable.text().to_string());
+            }
+        ));
+    }
+    // endregion: Preferences
+
+    // region: Glossary
+    fn open_glossary(&self) {
+        let Ok(profile) = self.selected_profile() else {
+            return;
+        };
+        let entries = Rc::new(RefCell::new(profile.glossary()));
+        let list = gtk::ListBox::builder()
+            .selection_mode(gtk::SelectionMode::None)
+            .css_classes(["boxed-list"])
+            .build();
+        fill_list(&list, &entries, glossary_fields, false, true);
+
+        let add_button = gtk::Button::builder()
+            .icon_name("list-add-symbolic")
+            .tooltip_text("Add Term")
+            .build();
+        add_button.connect_clicked(clone!(
+            #[weak]
+            list,
+            #[strong]
+            entries,
+            move |_| {
+                entries.borrow_mut().push(GlossaryEntry::default());
+                fill_list(&list, &entries, glossary_fields, false, true);
+            }
+        ));
+
+        let import_button = gtk::Button::builder().label("Import").build();
+        import_button.connect_clicked(clone!(
+            #[weak(rename_to = window)]
+            self,
+            #[weak]
+            list,
+            #[strong]
+            entries,
+            move |button| {
+                let parent = button.root().and_downcast::<gtk::Window>();
+                gtk::FileDialog::builder()
+                    .title("Import Glossary")
+                    .build()
+                    .open(
+                        parent.as_ref(),
+                        gio::Cancellable::NONE,
+                        clone!(
+                            #[weak]
+                            window,
+                            #[weak]
+                            list,
+                            #[strong]
+                            entries,
+                            move |file| {
+                                let Some(path) = file.ok().and_then(|file| file.path()) else {
+                                    return;
+                                };
+                                match glossary::import_csv(&path) {
+                                    Ok(imported) => {
+                                        entries.borrow_mut().extend(imported);
+                                        fill_list(&list, &entries, glossary_fields, false, true);
+                                    }
+                                    Err(err) => window
+                                        .dialog("Failed to import the glossary", &err.to_string()),
+                                }
+                            }
+                        ),
+                    );
+            }
+        ));
+
+        let export_button = gtk::Button::builder().label("Export").build();
+        export_button.connect_clicked(clone!(
+            #[weak(rename_to = window)]
+            self,
+            #[strong]
+            entries,
+            move |button| {
+                let parent = button.root().and_downcast::<gtk::Window>();
+                gtk::FileDialog::builder()
+                    .title("Export Glossary")
+                    .initial_name("glossary.csv")
+                    .build()
+                    .save(
+                        parent.as_ref(),
+                        gio::Cancellable::NONE,
+                        clone!(
+                            #[weak]
+                            window,
+                            #[strong]
+                            entries,
+                            move |file| {
+                                let Some(path) = file.ok().and_then(|file| file.path()) else {
+                                    return;
+                                };
+                                if let Err(err) = glossary::export_csv(&path, &entries.borrow()) {
+                                    window
+                                        .dialog("Failed to export the glossary", &err.to_string());
+                                }
+                            }
+                        ),
+                    );
+            }
+        ));
+
+        let header = adw::HeaderBar::builder()
+            .title_widget(&adw::WindowTitle::new("Glossary", &profile.title()))
+            .build();
+        header.pack_start(&add_button);
+        header.pack_end(&export_button);
+        header.pack_end(&import_button);
+
+        let glossary_window = dialog_window(self, "Glossary", &header, &list, 700, 500);
+        glossary_window.connect_close_request(move |_| {
+            let entries = entries
+                .borrow()
+                .iter()
+                .filter(|entry| !entry.source.trim().is_empty())
+                .cloned()
+                .collect();
+            profile.set_glossary(entries);
+            glib::Propagation::Proceed
+        });
+        glossary_window.present();
     }
+    // endregion: Glossary
 
-    fn setup_data(&self) {
-        self.setup_dd_ocr();
-        self.setup_dd_translation();
-        let _ = self.setup_dd_screen();
+    // region: OCR Cleanup
+    fn open_cleanup(&self) {
+        let Ok(profile) = self.selected_profile() else {
+            return;
+        };
+        let options = Rc::new(RefCell::new(profile.cleanup()));
+        let group = adw::PreferencesGroup::builder()
+            .title("OCR Cleanup")
+            .description("Applied to the recognized text before it is translated")
+            .build();
+        group.add(&switch_row(
+            "Unicode NFKC",
+            "Turns full width and compatibility characters into their usual form",
+            &options,
+            |options| &mut options.nfkc,
+        ));
+        group.add(&switch_row(
+            "Strip noise characters",
+            "Removes | and ¦ read from borders and cursors",
+            &options,
+            |options| &mut options.strip_noise,
+        ));
+        group.add(&switch_row(
+            "Collapse whitespace",
+            "Turns runs of spaces into one and trims every line",
+            &options,
+            |options| &mut options.collapse_whitespace,
+        ));
+        group.add(&switch_row(
+            "De-hyphenate",
+            "Joins words split with a hyphen at the end of a line",
+            &options,
+            |options| &mut options.dehyphenate,
+        ));
+        group.add(&switch_row(
+            "Join soft-wrapped lines",
+            "Joins lines broken by the text box, keeping sentence ends",
+            &options,
+            |options| &mut options.join_lines,
+        ));
 
-        self.navigate("main");
+        let cleanup_window =
+            dialog_window(self, "OCR Cleanup", &adw::HeaderBar::new(), &group, 500, -1);
+        cleanup_window.connect_close_request(move |_| {
+            profile.set_cleanup(*options.borrow());
+            glib::Propagation::Proceed
+        });
+        cleanup_window.present();
     }
+    // endregion: OCR Cleanup
 
-    fn setup_dd_ocr(&self) {
-        let languages = rusty_tesseract::get_tesseract_langs();
-        match languages {
-            Ok(values) => {
-                let list = ListStore::new::<OcrObject>();
-                for lang in &values {
-                    list.append(&OcrObject::new(lang.to_string()));
-                }
-                let expression = PropertyExpression::new(
-                    OcrObject::static_type(),
-                    Expression::NONE,
-                    "language"
-                );
-                self.imp().dd_ocr.set_expression(Some(expression));
-                self.imp().dd_ocr.set_model(Some(&list));
+    // region: Rewrite Rules
+    fn open_rewrite_rules(&self) {
+        let Ok(profile) = self.selected_profile() else {
+            return;
+        };
+        let rules = Rc::new(RefCell::new(profile.rewrite_rules()));
+        let list = gtk::ListBox::builder()
+            .selection_mode(gtk::SelectionMode::None)
+            .css_classes(["boxed-list"])
+            .build();
+        fill_list(&list, &rules, rewrite_fields, true, true);
+
+        let add_button = gtk::Button::builder()
+            .icon_name("list-add-symbolic")
+            .tooltip_text("Add Rule")
+            .build();
+        add_button.connect_clicked(clone!(
+            #[weak]
+            list,
+            #[strong]
+            rules,
+            move |_| {
+                rules.borrow_mut().push(RewriteRule::default());
+                fill_list(&list, &rules, rewrite_fields, true, true);
             }
-            Err(value) =>
-                self.dialog(
-                    "Can't find languages for translation",
-                    &format!("{value}\r\nPossible cause of the problem: Tesseract is not installed in your system. Please follow the instructions at https://tesseract-ocr.github.io/tessdoc/Installation.html")
-                ),
-        }
-    }
+        ));
 
-    fn setup_dd_translation(&self) {
-        let list = ListStore::new::<TranslatorObject>();
-        let all_langs = TranslatorData::all_languages();
-        for lang in &all_langs {
-            list.append(&TranslatorObject::new(lang.code.to_string()));
-        }
-        let expression = PropertyExpression::new(
-            TranslatorObject::static_type(),
-            Expression::NONE,
-            "language",
-        );
-        self.imp().dd_translation.set_expression(Some(expression));
-        self.imp().dd_translation.set_model(Some(&list));
+        let header = adw::HeaderBar::builder()
+            .title_widget(&adw::WindowTitle::new(
+                "Rewrite Rules",
+                "Regular expressions applied in order to the translated text",
+            ))
+            .build();
+        header.pack_start(&add_button);
+
+        let rules_window = dialog_window(self, "Rewrite Rules", &header, &list, 700, 500);
+        rules_window.connect_close_request(move |_| {
+            let rules = rules
+                .borrow()
+                .iter()
+                .filter(|rule| !rule.pattern.is_empty())
+                .cloned()
+                .collect();
+            profile.set_rewrite_rules(rules);
+            glib::Propagation::Proceed
+        });
+        rules_window.present();
     }
+    // endregion: Rewrite Rules
 
-    fn setup_dd_screen(&self) -> Result<()> {
-        let list = open_windows()?;
+    // region: Cache
+    fn open_cache(&self) {
+        let list = gtk::ListBox::builder()
+            .selection_mode(gtk::SelectionMode::None)
+            .css_classes(["boxed-list"])
+            .build();
+        let title = adw::WindowTitle::new("Translation Cache", "");
+        self.fill_cache_list(&list, &title);
 
-        let expression =
-            PropertyExpression::new(ScreenObject::static_type(), Expression::NONE, "title");
+        let clear_button = gtk::Button::builder()
+            .label("Clear")
+            .css_classes(["destructive-action"])
+            .build();
+        clear_button.connect_clicked(clone!(
+            #[weak(rename_to = window)]
+            self,
+            #[weak]
+            list,
+            #[weak]
+            title,
+            move |_| {
+                if let Err(err) = translation::cache::clear() {
+                    window.dialog("Failed to clear the cache", &err.to_string());
+                }
+                window.fill_cache_list(&list, &title);
+            }
+        ));
 
-        self.imp().dd_screen.set_expression(Some(expression));
-        self.imp().dd_screen.set_model(Some(&list));
+        let header = adw::HeaderBar::builder().title_widget(&title).build();
+        header.pack_start(&clear_button);
 
-        Ok(())
+        dialog_window(self, "Translation Cache", &header, &list, 600, 500).present();
     }
 
-    fn dialog(&self, message: &str, detail: &str) {
-        let dialog = gtk::AlertDialog::builder()
-            .detail(detail)
-            .message(message)
-            .modal(true)
-            .build();
-        dialog.show(Some(self))
+    fn fill_cache_list(&self, list: &gtk::ListBox, title: &adw::WindowTitle) {
+        while let Some(row) = list.first_child() {
+            list.remove(&row);
+        }
+        let entries = translation::cache::entries();
+        title.set_subtitle(&format!("{} entries", entries.len()));
+        for entry in entries {
+            let row = adw::ActionRow::builder()
+                .title(glib::markup_escape_text(&entry.key.text))
+                .subtitle(glib::markup_escape_text(&entry.translation))
+                .build();
+            row.add_suffix(&gtk::Label::new(Some(&format!(
+                "{} {} → {}",
+                entry.key.provider, entry.key.source, entry.key.target
+            ))));
+            list.append(&row);
+        }
     }
+    // endregion: Cache
 
     // region: Profiles
     fn profiles(&self) -> &ListStore {
@@ -460,12 +1641,12 @@ impl Window {
 
                     obj.title.set_text(&profile.title);
 
-                    let list = TranslatorData::all_languages();
-                    let id = list
-                        .iter()
-                        .position(|value| value.code.eq(&profile.translation))
-                        .unwrap_or(0);
-                    obj.dd_translation.set_selected(id as u32);
+                    window.select_translation(&profile.translation);
+                    window.select_model(&profile.ollama_model);
+                    obj.spin_temperature.set_value(profile.ollama_temperature);
+                    obj.entry_keep_alive.set_text(&profile.ollama_keep_alive);
+                    obj.spin_context.set_value(profile.context_lines as f64);
+                    obj.spin_confidence.set_value(profile.min_confidence as f64);
 
                     let list = rusty_tesseract::get_tesseract_langs()?;
                     let id = list
@@ -475,6 +1656,7 @@ impl Window {
                     obj.dd_ocr.set_selected(id as u32);
 
                     obj.chk_full_screen.set_active(profile.use_areas);
+                    obj.chk_paragraphs.set_active(profile.paragraphs);
 
                     let _ = window.setup_dd_screen();
                     let model = obj.dd_screen.model().expect("Failed to get model");
@@ -582,6 +1764,7 @@ impl Window {
                 translation: tra_lang.to_string(),
                 use_areas: self.imp().chk_full_screen.is_active(),
                 areas: vec![],
+                ..Default::default()
             }));
         Ok(())
     }
@@ -713,6 +1896,240 @@ impl Window {
         self.imp().drawing_area.add_controller(controller);
     }
 
+    fn setup_area_editor(&self) {
+        let controller = gtk::GestureClick::builder()
+            .button(gtk::gdk::BUTTON_SECONDARY)
+            .build();
+        controller.connect_pressed(clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_, _, x, y| {
+                if window.current_state() != State::Paused {
+                    return;
+                }
+                let Ok(areas) = window.translation_areas() else {
+                    return;
+                };
+                let (x, y) = (x as i32, y as i32);
+                let index = areas.iter().position(|area| {
+                    utils::value_in_range(x, area.x, area.x + area.width)
+                        && utils::value_in_range(y, area.y, area.y + area.height)
+                });
+                if let Some(index) = index {
+                    window.open_area_editor(index as u32, &areas[index]);
+                }
+            }
+        ));
+        self.imp().drawing_area.add_controller(controller);
+    }
+
+    // region: Area Editor
+    fn open_area_editor(&self, index: u32, area: &AreaData) {
+        let Ok(profile) = self.selected_profile() else {
+            return;
+        };
+        let preprocess = Rc::new(RefCell::new(area.preprocess));
+
+        let group = adw::PreferencesGroup::builder()
+            .title("Preprocessing")
+            .description("Applied to the captured area before OCR, in this order")
+            .build();
+        group.add(&switch_row(
+            "Grayscale",
+            "Drops the colors of the background",
+            &preprocess,
+            |preprocess| &mut preprocess.grayscale,
+        ));
+        group.add(&number_row(
+            "Scale",
+            "Small fonts are read better at 2 to 4 times their size",
+            &preprocess,
+            1.0..=Preprocess::MAX_SCALE as f64,
+            |preprocess| &mut preprocess.scale,
+        ));
+        group.add(&switch_row(
+            "Denoise",
+            "Removes isolated pixels from busy backgrounds",
+            &preprocess,
+            |preprocess| &mut preprocess.denoise,
+        ));
+        group.add(&switch_row(
+            "Sharpen",
+            "Makes blurry or scaled text edges crisper",
+            &preprocess,
+            |preprocess| &mut preprocess.sharpen,
+        ));
+
+        let threshold = gtk::SpinButton::with_range(0.0, 255.0, 1.0);
+        threshold.set_valign(gtk::Align::Center);
+        threshold.set_value(area.preprocess.threshold as f64);
+        threshold.set_sensitive(area.preprocess.binarize == Binarize::Threshold);
+        threshold.connect_value_changed(clone!(
+            #[strong]
+            preprocess,
+            move |spin| preprocess.borrow_mut().threshold = spin.value_as_int() as u8
+        ));
+        let threshold_row = adw::ActionRow::builder()
+            .title("Threshold")
+            .subtitle("Brighter pixels turn white, the others black")
+            .build();
+        threshold_row.add_suffix(&threshold);
+
+        group.add(&choice_row(
+            "Binarize",
+            "Turns the image black and white, implies grayscale",
+            Binarize::ALL.map(|binarize| (binarize, binarize.name().to_string())),
+            &area.preprocess.binarize,
+            clone!(
+                #[strong]
+                preprocess,
+                #[weak]
+                threshold,
+                move |binarize| {
+                    preprocess.borrow_mut().binarize = binarize;
+                    threshold.set_sensitive(binarize == Binarize::Threshold);
+                }
+            ),
+        ));
+        group.add(&threshold_row);
+        group.add(&switch_row(
+            "Invert",
+            "Tesseract expects dark text on a light background",
+            &preprocess,
+            |preprocess| &mut preprocess.invert,
+        ));
+
+        let ocr = Rc::new(RefCell::new(area.ocr.clone()));
+        let recognition = adw::PreferencesGroup::builder()
+            .title("Recognition")
+            .description("Tesseract options of this area, the profile's are used when unset")
+            .build();
+        let mut languages = vec![(String::new(), "Same as profile".to_string())];
+        if let Some(model) = self.imp().dd_ocr.model() {
+            languages.extend(
+                model
+                    .iter::<OcrObject>()
+                    .filter_map(Result::ok)
+                    .map(|language| (language.code(), language.language())),
+            );
+        }
+        recognition.add(&choice_row(
+            "Language",
+            "Lets one profile mix areas in different languages",
+            languages,
+            &area.ocr.language,
+            clone!(
+                #[strong]
+                ocr,
+                move |language| ocr.borrow_mut().language = language
+            ),
+        ));
+        recognition.add(&choice_row(
+            "Page Segmentation",
+            "Single line works best for menus and HUD labels",
+            std::iter::once((None, "Default".to_string()))
+                .chain(OcrOptions::PSM.map(|(psm, name)| (Some(psm), format!("{name} ({psm})")))),
+            &area.ocr.psm,
+            clone!(
+                #[strong]
+                ocr,
+                move |psm| ocr.borrow_mut().psm = psm
+            ),
+        ));
+        recognition.add(&choice_row(
+            "Engine",
+            "The legacy engine needs its own trained data",
+            std::iter::once((None, "Default".to_string()))
+                .chain(OcrOptions::OEM.map(|(oem, name)| (Some(oem), name.to_string()))),
+            &area.ocr.oem,
+            clone!(
+                #[strong]
+                ocr,
+                move |oem| ocr.borrow_mut().oem = oem
+            ),
+        ));
+
+        let dpi = gtk::SpinButton::with_range(0.0, 1200.0, 10.0);
+        dpi.set_valign(gtk::Align::Center);
+        dpi.set_value(area.ocr.dpi.unwrap_or_default() as f64);
+        dpi.connect_value_changed(clone!(
+            #[strong]
+            ocr,
+            move |spin| ocr.borrow_mut().dpi = Some(spin.value_as_int()).filter(|dpi| *dpi > 0)
+        ));
+        let dpi_row = adw::ActionRow::builder()
+            .title("DPI")
+            .subtitle("Resolution Tesseract assumes, 0 keeps the default")
+            .build();
+        dpi_row.add_suffix(&dpi);
+        recognition.add(&dpi_row);
+
+        let whitelist = adw::EntryRow::builder()
+            .title("Only recognize these characters")
+            .text(&area.ocr.whitelist)
+            .build();
+        whitelist.connect_changed(clone!(
+            #[strong]
+            ocr,
+            move |entry| ocr.borrow_mut().whitelist = entry.text().to_string()
+        ));
+        recognition.add(&whitelist);
+        let blacklist = adw::EntryRow::builder()
+            .title("Never recognize these characters")
+            .text(&area.ocr.blacklist)
+            .build();
+        blacklist.connect_changed(clone!(
+            #[strong]
+            ocr,
+            move |entry| ocr.borrow_mut().blacklist = entry.text().to_string()
+        ));
+        recognition.add(&blacklist);
+
+        let stable_captures = Rc::new(RefCell::new(area.stable_captures));
+        let stability = adw::PreferencesGroup::builder().title("Stability").build();
+        stability.add(&number_row(
+            "Stable Captures",
+            "Waits for the area to stay the same for this many captures before reading it, for text revealed one character at a time",
+            &stable_captures,
+            0.0..=MAX_STABLE_CAPTURES as f64,
+            |captures| captures,
+        ));
+
+        let groups = gtk::Box::new(gtk::Orientation::Vertical, 24);
+        groups.append(&group);
+        groups.append(&recognition);
+        groups.append(&stability);
+
+        let header = adw::HeaderBar::builder()
+            .title_widget(&adw::WindowTitle::new(
+                &format!("Area {}", index + 1),
+                &format!("{}x{} at {}, {}", area.width, area.height, area.x, area.y),
+            ))
+            .build();
+
+        // The overlay covers the screen while configuring, the editor goes above it.
+        let parent = self
+            .imp()
+            .drawing_area
+            .root()
+            .and_downcast::<gtk::Window>()
+            .unwrap_or_else(|| self.clone().upcast());
+        let editor = dialog_window(&parent, "Area", &header, &groups, 500, 700);
+        editor.connect_close_request(move |_| {
+            let area_object = profile.areas().item(index).and_downcast::<AreaObject>();
+            if let Some(area_object) = area_object {
+                let mut area = area_object.area_data();
+                area.preprocess = *preprocess.borrow();
+                area.ocr = ocr.borrow().clone();
+                area.stable_captures = *stable_captures.borrow();
+                area_object.set_area_data(area);
+            }
+            glib::Propagation::Proceed
+        });
+        editor.present();
+    }
+    // endregion: Area Editor
+
     fn draw_rectagles(&self, areas: Vec<AreaData>) {
         self.imp()
             .drawing_area
@@ -793,6 +2210,9 @@ impl Window {
     }
 
     fn stop(&self) -> State {
+        if let Some(cancel) = self.imp().cancel.take() {
+            let _ = cancel.send(true);
+        }
         let _ = WindowManager::close_window(WINDOW_NAME);
         State::Stopped
     }
@@ -817,12 +2237,15 @@ impl Window {
         match state {
             State::Started => {
                 obj.chk_full_screen.set_sensitive(false);
+                obj.chk_paragraphs.set_sensitive(false);
                 obj.config_button.set_sensitive(false);
                 obj.remove_button.set_sensitive(false);
                 obj.action_button.set_label("Stop");
             }
             State::Stopped => {
                 obj.chk_full_screen.set_sensitive(true);
+                obj.chk_paragraphs
+                    .set_sensitive(obj.chk_full_screen.is_active());
                 obj.config_button
                     .set_sensitive(!obj.chk_full_screen.is_active());
                 obj.action_button.set_sensitive(true);
@@ -832,6 +2255,7 @@ impl Window {
             }
             State::Paused => {
                 obj.chk_full_screen.set_sensitive(false);
+                obj.chk_paragraphs.set_sensitive(false);
                 obj.action_button.set_sensitive(false);
                 obj.remove_button.set_sensitive(false);
                 obj.config_button.set_label("Stop configuring");
@@ -841,80 +2265,200 @@ impl Window {
     }
 
     fn text_overlay(&self) -> Result<()> {
+        // Checked before the session counts as running, a bad rule or term stops it here.
+        let rewriter = Rewriter::new(&self.selected_profile()?.rewrite_rules())?;
+        let glossary = Glossary::new(&self.selected_profile()?.glossary())?;
         let obj = self.imp();
         obj.running.replace(true);
         obj.status_label.set_text("Running");
+        self.update_usage();
 
         let ocr = self.ocr_data()?;
         let is_vertical = ocr.is_vertical;
         let screen = self.screen_data()?;
-        let translator = self.translator_data()?.code;
+        let target = self.translator_data()?.code;
         let settings = self.settings();
-        let provider = settings.tra_provider().to_string();
+        let profile = self.selected_profile()?.to_profile_data();
+        let chain = Chain::new(&settings, &profile);
+        let cleanup = profile.cleanup;
+        let context_lines = profile.context_lines as usize;
         let areas = self.translation_areas()?;
         let is_areas = !obj.chk_full_screen.is_active();
+        let paragraphs = profile.paragraphs;
+        let min_confidence = profile.min_confidence as f32;
+
+        let (cancel_tx, mut cancel_rx) = watch::channel(false);
+        obj.cancel.replace(Some(cancel_tx));
 
         let (tx, mut rx) = mpsc::channel(1);
         thread::spawn(move || {
             let rc = tokio::runtime::Runtime::new().unwrap();
             rc.block_on(async {
-                let client = match translation::client().await {
-                    Ok(value) => value,
-                    Err(err) => {
+                let mut history: Vec<VecDeque<Line>> = vec![];
+                // Last capture of each area, or of the window, and what is shown for it.
+                let mut hashes: Vec<Option<ImageHash>> = vec![];
+                let mut shown: Vec<AreaData> = vec![];
+                // Previous capture of each area and how many captures in a row matched it.
+                let mut previous: Vec<Option<ImageHash>> = vec![];
+                let mut stable: Vec<u32> = vec![];
+                'capture: while !tx.is_closed() && !*cancel_rx.borrow() {
+                    let images = if is_areas {
+                        screen.capture_areas(&areas)
+                    } else {
+                        screen.capture().map(|image| vec![image])
+                    };
+                    if let Err(err) = images {
                         let _ = tx.send(Err(anyhow::anyhow!(err.to_string()))).await;
-                        return;
+                        break;
+                    }
+                    let images = images.unwrap();
+                    let new_hashes = images.iter().map(ImageHash::new).collect::<Vec<_>>();
+                    hashes.resize(images.len(), None);
+                    previous.resize(images.len(), None);
+                    stable.resize(images.len(), 0);
+                    for (i, hash) in new_hashes.iter().enumerate() {
+                        let same = previous[i]
+                            .as_ref()
+                            .is_some_and(|capture| capture.matches(hash));
+                        stable[i] = if same { stable[i].saturating_add(1) } else { 0 };
+                        previous[i] = Some(hash.clone());
+                    }
+                    // Unchanged captures keep their text and translation without OCR, text
+                    // still being revealed keeps the previous one until it settles.
+                    let changed = (0..images.len())
+                        .filter(|i| {
+                            hashes[*i]
+                                .as_ref()
+                                .is_none_or(|hash| !hash.matches(&new_hashes[*i]))
+                        })
+                        .filter(|i| !is_areas || stable[*i] >= areas[*i].stable_captures)
+                        .collect::<Vec<usize>>();
+                    if changed.is_empty() {
+                        tokio::select! {
+                            _ = sleep(Duration::from_millis(TRANSLATION_DELAY)) => {}
+                            _ = cancel_rx.changed() => break,
+                        }
+                        continue;
                     }
-                };
 
-                while !tx.is_closed() {
-                    let areas = if is_areas {
-                        ocr.ocr_areas(&areas, &screen)
+                    let areas_read = if is_areas {
+                        images
+                            .into_iter()
+                            .enumerate()
+                            .filter(|(i, _)| changed.contains(i))
+                            .map(|(i, image)| ocr.ocr_area(image, &areas[i]))
+                            .collect::<Result<Vec<AreaData>>>()
                     } else {
-                        ocr.ocr_screen(&screen)
+                        images
+                            .into_iter()
+                            .next()
+                            .map_or(Ok(vec![]), |image| {
+                                ocr.ocr_screen(image, paragraphs)
+                            })
                     };
-
-                    if let Err(err) = areas {
+                    if let Err(err) = areas_read {
                         let _ = tx.send(Err(anyhow::anyhow!(err.to_string()))).await;
-                        return;
+                        break;
+                    }
+                    let mut areas_read = areas_read.unwrap();
+                    for area in areas_read.iter_mut() {
+                        area.text = cleanup::clean(&area.text, &cleanup);
                     }
-                    let mut areas = areas.unwrap();
-
-                    if translator != "nt" {
-                        for area in &mut areas {
-                            sleep(Duration::from_millis(200)).await;
-                            let result = match provider.as_str() {
-                                "google" => {
-                                    translation::google(
-                                        &client,
-                                        &area.text,
-                                        &ocr.to_translator().code,
-                                        &translator,
-                                    )
-                                    .await
+                    // Where the context of each area read is kept. Whole window regions
+                    // move from one capture to the next, so they share one history.
+                    let slots = if is_areas {
+                        changed.clone()
+                    } else {
+                        vec![0; areas_read.len()]
+                    };
+
+                    // A timed out translation skips the frame but still waits for the next one.
+                    'frame: {
+                        if target != "nt" {
+                            let protected = areas_read
+                                .iter()
+                                .map(|area| glossary.protect(&area.text))
+                                .collect::<Vec<Protected>>();
+                            history.resize_with(
+                                if is_areas { areas.len() } else { 1 },
+                                VecDeque::new,
+                            );
+                            let mut translated = vec![(String::new(), ""); areas_read.len()];
+                            // Areas read in another language are translated from it.
+                            for (source, mut indexes) in group_by_source(&areas_read, &ocr) {
+                                // Uncertain areas are likely noise, they are shown but not sent.
+                                indexes.retain(|i| areas_read[*i].confidence >= min_confidence);
+                                if indexes.is_empty() {
+                                    continue;
                                 }
-                                _ => {
-                                    translation::deepl(
-                                        &client,
-                                        &area.text.clone(),
-                                        &ocr.to_translator().code,
-                                        &translator.clone(),
-                                    )
-                                    .await
+                                let texts = indexes
+                                    .iter()
+                                    .map(|i| protected[*i].text.clone())
+                                    .collect::<Vec<String>>();
+                                let context = indexes
+                                    .iter()
+                                    .map(|i| history[slots[*i]].iter().cloned().collect())
+                                    .collect::<Vec<Vec<Line>>>();
+                                let result = tokio::select! {
+                                    result = chain.translate(&texts, &context, &source, &target) => result,
+                                    _ = cancel_rx.changed() => break 'capture,
+                                };
+                                if let Err(err) = result {
+                                    if err.is::<TimedOut>() {
+                                        let _ = tx.send(Ok(Frame::Skipped(err.to_string()))).await;
+                                        break 'frame;
+                                    }
+                                    let _ = tx.send(Err(anyhow::anyhow!(err.to_string()))).await;
+                                    break 'capture;
                                 }
-                            };
-                            if let Err(err) = result {
-                                let _ = tx.send(Err(anyhow::anyhow!(err.to_string()))).await;
-                                return;
+                                let (texts, provider) = result.unwrap();
+                                for (i, text) in indexes.into_iter().zip(texts) {
+                                    translated[i] = (text, provider);
+                                }
+                            }
+                            for ((area, (text, provider)), (protected, slot)) in areas_read
+                                .iter_mut()
+                                .zip(translated)
+                                .zip(protected.iter().zip(&slots))
+                            {
+                                if area.confidence < min_confidence {
+                                    continue;
+                                }
+                                let translation = rewriter.apply(&protected.restore(&text));
+                                remember(&mut history[*slot], context_lines, &area.text, &translation);
+                                area.text = translation;
+                                area.provider = provider.to_string();
                             }
-                            area.text = result.unwrap();
                         }
-                    }
 
-                    let _ = tx.send(Ok(areas)).await;
-                    sleep(Duration::from_millis(TRANSLATION_DELAY)).await;
+                        if is_areas {
+                            shown.resize_with(areas.len(), AreaData::default);
+                            for (i, area) in changed.iter().zip(areas_read) {
+                                shown[*i] = area;
+                            }
+                        } else {
+                            shown = areas_read;
+                        }
+                        for (i, hash) in new_hashes.into_iter().enumerate() {
+                            if changed.contains(&i) {
+                                hashes[i] = Some(hash);
+                            }
+                        }
+                        let _ = tx.send(Ok(Frame::Translated(shown.clone()))).await;
+                    }
+                    tokio::select! {
+                        _ = sleep(Duration::from_millis(TRANSLATION_DELAY)) => {}
+                        _ = cancel_rx.changed() => break,
+                    }
                 }
 
-                let _ = client.close().await;
+                chain.close().await;
+                if let Err(err) = translation::cache::save() {
+                    println!("Failed to save the translation cache: {err:?}");
+                }
+                if let Err(err) = translation::limiter::save() {
+                    println!("Failed to save the usage counters: {err:?}");
+                }
             });
         });
 
@@ -927,13 +2471,20 @@ impl Window {
                         break;
                     };
                     let areas = match message {
-                        Ok(value) => value,
+                        Ok(Frame::Translated(value)) => value,
+                        Ok(Frame::Skipped(reason)) => {
+                            let status = format!("Running, skipped a capture: {reason}");
+                            window.imp().status_label.set_text(&status);
+                            continue;
+                        }
                         Err(err) => {
                             window.error_dialog(&err.to_string());
                             break;
                         }
                     };
-                    window.draw_text(areas, is_vertical);
+                    window.imp().status_label.set_text("Running");
+                    window.draw_text(areas, is_vertical, min_confidence);
+                    window.update_usage();
                 }
                 rx.close();
                 window.imp().running.replace(false);
@@ -944,23 +2495,442 @@ impl Window {
         Ok(())
     }
 
+    /// Shows this month's consumption of the providers in the chain and warns
+    /// once when one of them gets close to its quota.
+    fn update_usage(&self) {
+        let settings = self.settings().clone();
+        let mut lines = vec![];
+        for id in settings.tra_chain() {
+            let name = translation::provider_name(id);
+            let limits = settings.limits(id);
+            let used = translation::limiter::used(id);
+            if limits.monthly_characters > 0 {
+                lines.push(format!(
+                    "{name}: {used} of {} characters this month",
+                    limits.monthly_characters
+                ));
+            } else if used > 0 {
+                lines.push(format!("{name}: {used} characters this month"));
+            }
+            if translation::limiter::should_warn(id, limits) {
+                self.dialog(
+                    "Translation quota almost reached",
+                    &format!(
+                        "{name} used {used} of its {} characters this month",
+                        limits.monthly_characters
+                    ),
+                );
+            }
+        }
+        let usage_label = &self.imp().usage_label;
+        usage_label.set_text(&lines.join("\n"));
+        usage_label.set_visible(!lines.is_empty());
+    }
+
     fn error_dialog(&self, message: &str) {
         self.change_state(self.stop());
         self.dialog("Text Overlay Error", message);
     }
 
-    fn draw_text(&self, areas: Vec<AreaData>, vertical: bool) {
+    fn draw_text(&self, areas: Vec<AreaData>, vertical: bool, min_confidence: f32) {
         let obj = self.imp();
         obj.drawing_area.queue_draw();
         obj.drawing_area
             .set_draw_func(move |_, cr, _width, _height| {
                 for area in areas.iter() {
-                    let _ = paint::draw_fitted_text_with_background(cr, area);
+                    let _ = if area.confidence < min_confidence {
+                        paint::draw_uncertain(cr, area)
+                    } else {
+                        paint::draw_fitted_text_with_background(cr, area)
+                    };
                 }
             });
     }
 }
 
+/// Items edited by the rows of a list, shared with their widgets.
+type ListItems<T> = Rc<RefCell<Vec<T>>>;
+
+/// Refills `list` with a row per item, holding the widgets `fields` builds
+/// for it followed by the buttons moving and removing it.
+fn fill_list<T: 'static>(
+    list: &gtk::ListBox,
+    items: &ListItems<T>,
+    fields: fn(&ListItems<T>, usize, &T) -> Vec<gtk::Widget>,
+    orderable: bool,
+    removable: bool,
+) {
+    while let Some(row) = list.first_child() {
+        list.remove(&row);
+    }
+    let count = items.borrow().len();
+    for (index, item) in items.borrow().iter().enumerate() {
+        let row = gtk::Box::builder()
+            .spacing(6)
+            .margin_top(6)
+            .margin_bottom(6)
+            .margin_start(6)
+            .margin_end(6)
+            .build();
+        for field in fields(items, index, item) {
+            row.append(&field);
+        }
+
+        if orderable {
+            let up = gtk::Button::builder()
+                .icon_name("go-up-symbolic")
+                .tooltip_text("Move Up")
+                .css_classes(["flat"])
+                .sensitive(index > 0)
+                .build();
+            up.connect_clicked(clone!(
+                #[weak]
+                list,
+                #[strong]
+                items,
+                move |_| {
+                    items.borrow_mut().swap(index - 1, index);
+                    fill_list(&list, &items, fields, orderable, removable);
+                }
+            ));
+            row.append(&up);
+
+            let down = gtk::Button::builder()
+                .icon_name("go-down-symbolic")
+                .tooltip_text("Move Down")
+                .css_classes(["flat"])
+                .sensitive(index + 1 < count)
+                .build();
+            down.connect_clicked(clone!(
+                #[weak]
+                list,
+                #[strong]
+                items,
+                move |_| {
+                    items.borrow_mut().swap(index, index + 1);
+                    fill_list(&list, &items, fields, orderable, removable);
+                }
+            ));
+            row.append(&down);
+        }
+
+        if removable {
+            let remove = gtk::Button::builder()
+                .icon_name("user-trash-symbolic")
+                .tooltip_text("Remove")
+                .css_classes(["flat"])
+                .build();
+            remove.connect_clicked(clone!(
+                #[weak]
+                list,
+                #[strong]
+                items,
+                move |_| {
+                    items.borrow_mut().remove(index);
+                    fill_list(&list, &items, fields, orderable, removable);
+                }
+            ));
+            row.append(&remove);
+        }
+        list.append(&row);
+    }
+}
+
+fn glossary_fields(
+    entries: &ListItems<GlossaryEntry>,
+    index: usize,
+    entry: &GlossaryEntry,
+) -> Vec<gtk::Widget> {
+    let source = gtk::Entry::builder()
+        .text(&entry.source)
+        .placeholder_text("Term")
+        .hexpand(true)
+        .build();
+    source.connect_changed(clone!(
+        #[strong]
+        entries,
+        move |source| {
+            if let Some(entry) = entries.borrow_mut().get_mut(index) {
+                entry.source = source.text().to_string();
+            }
+        }
+    ));
+
+    let target = gtk::Entry::builder()
+        .text(&entry.target)
+        .placeholder_text("Translation")
+        .hexpand(true)
+        .build();
+    target.connect_changed(clone!(
+        #[strong]
+        entries,
+        move |target| {
+            if let Some(entry) = entries.borrow_mut().get_mut(index) {
+                entry.target = target.text().to_string();
+            }
+        }
+    ));
+
+    let case_sensitive = gtk::CheckButton::builder()
+        .label("Match case")
+        .active(entry.case_sensitive)
+        .build();
+    case_sensitive.connect_toggled(clone!(
+        #[strong]
+        entries,
+        move |check| {
+            if let Some(entry) = entries.borrow_mut().get_mut(index) {
+                entry.case_sensitive = check.is_active();
+            }
+        }
+    ));
+
+    let whole_word = gtk::CheckButton::builder()
+        .label("Whole word")
+        .active(entry.whole_word)
+        .build();
+    whole_word.connect_toggled(clone!(
+        #[strong]
+        entries,
+        move |check| {
+            if let Some(entry) = entries.borrow_mut().get_mut(index) {
+                entry.whole_word = check.is_active();
+            }
+        }
+    ));
+
+    vec![
+        source.upcast(),
+        target.upcast(),
+        case_sensitive.upcast(),
+        whole_word.upcast(),
+    ]
+}
+
+fn rewrite_fields(
+    rules: &ListItems<RewriteRule>,
+    index: usize,
+    rule: &RewriteRule,
+) -> Vec<gtk::Widget> {
+    let pattern = gtk::Entry::builder()
+        .text(&rule.pattern)
+        .placeholder_text("Pattern")
+        .hexpand(true)
+        .build();
+    mark_invalid_pattern(&pattern);
+    pattern.connect_changed(clone!(
+        #[strong]
+        rules,
+        move |pattern| {
+            mark_invalid_pattern(pattern);
+            if let Some(rule) = rules.borrow_mut().get_mut(index) {
+                rule.pattern = pattern.text().to_string();
+            }
+        }
+    ));
+
+    let replacement = gtk::Entry::builder()
+        .text(&rule.replacement)
+        .placeholder_text("Replacement, $1 for groups")
+        .hexpand(true)
+        .build();
+    replacement.connect_changed(clone!(
+        #[strong]
+        rules,
+        move |replacement| {
+            if let Some(rule) = rules.borrow_mut().get_mut(index) {
+                rule.replacement = replacement.text().to_string();
+            }
+        }
+    ));
+
+    vec![pattern.upcast(), replacement.upcast()]
+}
+
+fn fallback_fields(
+    providers: &ListItems<Fallback>,
+    index: usize,
+    provider: &Fallback,
+) -> Vec<gtk::Widget> {
+    let enabled = gtk::CheckButton::builder()
+        .label(&provider.name)
+        .active(provider.enabled)
+        .hexpand(true)
+        .build();
+    enabled.connect_toggled(clone!(
+        #[strong]
+        providers,
+        move |check| {
+            if let Some(provider) = providers.borrow_mut().get_mut(index) {
+                provider.enabled = check.is_active();
+            }
+        }
+    ));
+
+    vec![enabled.upcast()]
+}
+
+/// Modal window with `header` above `child`, which scrolls and keeps a
+/// readable width.
+fn dialog_window(
+    parent: &impl IsA<gtk::Window>,
+    title: &str,
+    header: &adw::HeaderBar,
+    child: &impl IsA<gtk::Widget>,
+    width: i32,
+    height: i32,
+) -> adw::Window {
+    let content = gtk::Box::new(gtk::Orientation::Vertical, 0);
+    content.append(header);
+    content.append(
+        &gtk::ScrolledWindow::builder()
+            .vexpand(true)
+            .propagate_natural_height(true)
+            .child(
+                &adw::Clamp::builder()
+                    .margin_top(12)
+                    .margin_bottom(12)
+                    .margin_start(12)
+                    .margin_end(12)
+                    .child(child)
+                    .build(),
+            )
+            .build(),
+    );
+    adw::Window::builder()
+        .title(title)
+        .modal(true)
+        .transient_for(parent)
+        .default_width(width)
+        .default_height(height)
+        .content(&content)
+        .build()
+}
+
+fn switch_row<T: 'static>(
+    title: &str,
+    subtitle: &str,
+    options: &Rc<RefCell<T>>,
+    field: fn(&mut T) -> &mut bool,
+) -> adw::ActionRow {
+    let switch = gtk::Switch::builder()
+        .active(*field(&mut options.borrow_mut()))
+        .valign(gtk::Align::Center)
+        .build();
+    switch.connect_active_notify(clone!(
+        #[strong]
+        options,
+        move |switch| *field(&mut options.borrow_mut()) = switch.is_active()
+    ));
+    let row = adw::ActionRow::builder()
+        .title(title)
+        .subtitle(subtitle)
+        .activatable_widget(&switch)
+        .build();
+    row.add_suffix(&switch);
+    row
+}
+
+/// Combo row over `choices`, each a value and the name shown for it.
+fn choice_row<T: Clone + PartialEq + 'static>(
+    title: &str,
+    subtitle: &str,
+    choices: impl IntoIterator<Item = (T, String)>,
+    value: &T,
+    set: impl Fn(T) + 'static,
+) -> adw::ComboRow {
+    let (values, names): (Vec<T>, Vec<String>) = choices.into_iter().unzip();
+    let names = names.iter().map(String::as_str).collect::<Vec<&str>>();
+    let row = adw::ComboRow::builder()
+        .title(title)
+        .subtitle(subtitle)
+        .model(&gtk::StringList::new(&names))
+        .build();
+    if let Some(position) = values.iter().position(|choice| choice == value) {
+        row.set_selected(position as u32);
+    }
+    row.connect_selected_notify(move |row| {
+        if let Some(value) = values.get(row.selected() as usize) {
+            set(value.clone());
+        }
+    });
+    row
+}
+
+fn number_row<T: 'static>(
+    title: &str,
+    subtitle: &str,
+    options: &Rc<RefCell<T>>,
+    range: std::ops::RangeInclusive<f64>,
+    field: fn(&mut T) -> &mut u32,
+) -> adw::ActionRow {
+    let spin = gtk::SpinButton::with_range(*range.start(), *range.end(), 1.0);
+    spin.set_valign(gtk::Align::Center);
+    spin.set_value(*field(&mut options.borrow_mut()) as f64);
+    spin.connect_value_changed(clone!(
+        #[strong]
+        options,
+        move |spin| *field(&mut options.borrow_mut()) = spin.value_as_int() as u32
+    ));
+    let row = adw::ActionRow::builder()
+        .title(title)
+        .subtitle(subtitle)
+        .build();
+    row.add_suffix(&spin);
+    row
+}
+
+fn mark_invalid_pattern(entry: &gtk::Entry) {
+    if regex::Regex::new(&entry.text()).is_ok() {
+        entry.remove_css_class("error");
+    } else {
+        entry.add_css_class("error");
+    }
+}
+
+/// Indexes of the areas translated from each source language.
+fn group_by_source(areas: &[AreaData], ocr: &OcrData) -> Vec<(String, Vec<usize>)> {
+    let mut groups: Vec<(String, Vec<usize>)> = vec![];
+    for (i, area) in areas.iter().enumerate() {
+        let source = OcrData::new(area.ocr.language(&ocr.code))
+            .to_translator()
+            .code;
+        match groups.iter_mut().find(|(code, _)| *code == source) {
+            Some((_, indexes)) => indexes.push(i),
+            None => groups.push((source, vec![i])),
+        }
+    }
+    groups
+}
+
+/// Adds a line to a history, skipping the lines it already holds like the
+/// repeated captures of a text that is still on screen.
+fn remember(lines: &mut VecDeque<Line>, limit: usize, source: &str, target: &str) {
+    let source = source.trim();
+    if limit == 0 || source.is_empty() || lines.iter().any(|line| line.source == source) {
+        return;
+    }
+    lines.push_back(Line {
+        source: source.to_string(),
+        target: target.trim().to_string(),
+    });
+    while lines.len() > limit {
+        lines.pop_front();
+    }
+}
+
+/// Runs `task` on its own runtime thread and hands back the result.
+fn spawn_task<T: Send + 'static>(
+    task: impl Future<Output = T> + Send + 'static,
+) -> mpsc::Receiver<T> {
+    let (tx, rx) = mpsc::channel(1);
+    thread::spawn(move || {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let _ = tx.blocking_send(rt.block_on(task));
+    });
+    rx
+}
+
 fn open_windows() -> Result<ListStore> {
     let list = ListStore::new::<ScreenObject>();
     let windows = xcap::Window::all()?;